@@ -0,0 +1,68 @@
+use std::{mem, ops::RangeInclusive};
+
+use proptest::{
+    arbitrary::{any_with, Arbitrary},
+    collection::{vec, SizeRange, VecStrategy},
+    strategy::{Map, Strategy},
+};
+
+use crate::{
+    bit_num::{Type, Underlying},
+    magic::bits_to_bytes,
+    Bit, BitLayout, BitType, BitVec, I, U,
+};
+
+impl<const N: usize> Arbitrary for U<N>
+where
+    Underlying<N>: Type,
+    RangeInclusive<<Underlying<N> as Type>::U>: Strategy<Value = <Underlying<N> as Type>::U>,
+{
+    type Parameters = ();
+    type Strategy =
+        Map<RangeInclusive<<Underlying<N> as Type>::U>, fn(<Underlying<N> as Type>::U) -> Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (Self::MIN.extract_underlying()..=Self::MAX.extract_underlying())
+            .prop_map(U::new as fn(_) -> _)
+    }
+}
+
+impl<const N: usize> Arbitrary for I<N>
+where
+    Underlying<N>: Type,
+    RangeInclusive<<Underlying<N> as Type>::I>: Strategy<Value = <Underlying<N> as Type>::I>,
+{
+    type Parameters = ();
+    type Strategy =
+        Map<RangeInclusive<<Underlying<N> as Type>::I>, fn(<Underlying<N> as Type>::I) -> Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (Self::MIN.extract_underlying()..=Self::MAX.extract_underlying())
+            .prop_map(I::new as fn(_) -> _)
+    }
+}
+
+impl<T: BitLayout + Arbitrary> Arbitrary for Bit<T>
+where
+    [u8; bits_to_bytes(T::BITS)]: Sized,
+    [u8; mem::size_of::<T>()]: Sized,
+{
+    type Parameters = T::Parameters;
+    type Strategy = Map<T::Strategy, fn(T) -> Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        any_with::<T>(args).prop_map(Bit::from as fn(_) -> _)
+    }
+}
+
+impl<T: BitType + Arbitrary> Arbitrary for BitVec<T>
+where
+    [u8; bits_to_bytes(T::BITS)]: Sized,
+{
+    type Parameters = (SizeRange, T::Parameters);
+    type Strategy = Map<VecStrategy<T::Strategy>, fn(Vec<T>) -> Self>;
+
+    fn arbitrary_with((size, args): Self::Parameters) -> Self::Strategy {
+        vec(any_with::<T>(args), size).prop_map(|elements| elements.into_iter().collect())
+    }
+}
@@ -0,0 +1,148 @@
+use num_traits::{NumCast, ToPrimitive};
+
+use super::{max_u128, Type, Underlying, I, U};
+use crate::{bit_type::BitType, layout::leaf_layout, BitLayout, Encoding, Shape};
+
+fn unsigned_to_u128<const N: usize>(value: U<N>) -> u128
+where
+    Underlying<N>: Type,
+{
+    value.extract_underlying().to_u128().unwrap()
+}
+
+fn unsigned_from_u128<const N: usize>(bits: u128) -> U<N>
+where
+    Underlying<N>: Type,
+{
+    U::new(NumCast::from(bits & max_u128(N)).unwrap())
+}
+
+fn signed_to_i128<const N: usize>(value: I<N>) -> i128
+where
+    Underlying<N>: Type,
+{
+    value.extract_underlying().to_i128().unwrap()
+}
+
+fn signed_from_i128<const N: usize>(value: i128) -> I<N>
+where
+    Underlying<N>: Type,
+{
+    I::new(NumCast::from(value).unwrap())
+}
+
+/// A signed number stored with zigzag encoding, which maps small magnitudes to small
+/// unsigned numbers: `0, -1, 1, -2, 2, ...` are stored as `0, 1, 2, 3, 4, ...`.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ZigZag<T>(pub T);
+
+impl<const N: usize> BitType for ZigZag<I<N>>
+where
+    Underlying<N>: Type,
+    U<N>: BitType,
+{
+    const BITS: usize = N;
+
+    fn from_aligned(aligned: &Self, slice: &mut [u8], offset: usize) {
+        let value = signed_to_i128(aligned.0);
+        let bits = ((value << 1) ^ (value >> 127)) as u128;
+        U::<N>::from_aligned(&unsigned_from_u128(bits), slice, offset)
+    }
+
+    fn to_aligned(slice: &[u8], offset: usize) -> Self {
+        let bits = unsigned_to_u128(U::<N>::to_aligned(slice, offset));
+        ZigZag(signed_from_i128(
+            ((bits >> 1) as i128) ^ -((bits & 1) as i128),
+        ))
+    }
+}
+
+impl<const N: usize> BitLayout for ZigZag<I<N>>
+where
+    Underlying<N>: Type,
+    Self: BitType,
+{
+    leaf_layout!(Shape::Encoded(Encoding::ZigZag));
+}
+
+/// An unsigned number stored as reflected binary Gray code, where consecutive numbers only
+/// differ by a single bit.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Gray<T>(pub T);
+
+impl<const N: usize> BitType for Gray<U<N>>
+where
+    Underlying<N>: Type,
+    U<N>: BitType,
+{
+    const BITS: usize = N;
+
+    fn from_aligned(aligned: &Self, slice: &mut [u8], offset: usize) {
+        let value = unsigned_to_u128(aligned.0);
+        U::<N>::from_aligned(&unsigned_from_u128(value ^ (value >> 1)), slice, offset)
+    }
+
+    fn to_aligned(slice: &[u8], offset: usize) -> Self {
+        let mut value = unsigned_to_u128(U::<N>::to_aligned(slice, offset));
+        let mut shift = 1;
+        while shift < 128 {
+            value ^= value >> shift;
+            shift <<= 1;
+        }
+        Gray(unsigned_from_u128(value))
+    }
+}
+
+impl<const N: usize> BitLayout for Gray<U<N>>
+where
+    Underlying<N>: Type,
+    Self: BitType,
+{
+    leaf_layout!(Shape::Encoded(Encoding::Gray));
+}
+
+/// A signed number stored as sign and magnitude, the highest bit is the sign and the remaining
+/// `N - 1` bits are the magnitude. `I::<N>::MIN` can't be represented, and negative zero is read as zero.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SignMag<const N: usize>(pub I<N>)
+where
+    Underlying<N>: Type;
+
+impl<const N: usize> BitType for SignMag<N>
+where
+    Underlying<N>: Type,
+    U<N>: BitType,
+{
+    const BITS: usize = N;
+
+    fn from_aligned(aligned: &Self, slice: &mut [u8], offset: usize) {
+        let value = signed_to_i128(aligned.0);
+        debug_assert!(
+            value != signed_to_i128(I::<N>::MIN),
+            "{} can't be represented with sign and magnitude in {} bits",
+            value,
+            N
+        );
+        let sign = ((value < 0) as u128) << (N - 1);
+        let bits = sign | (value.unsigned_abs() & max_u128(N - 1));
+        U::<N>::from_aligned(&unsigned_from_u128(bits), slice, offset)
+    }
+
+    fn to_aligned(slice: &[u8], offset: usize) -> Self {
+        let bits = unsigned_to_u128(U::<N>::to_aligned(slice, offset));
+        let magnitude = (bits & max_u128(N - 1)) as i128;
+        if bits >> (N - 1) & 1 == 1 {
+            SignMag(signed_from_i128(-magnitude))
+        } else {
+            SignMag(signed_from_i128(magnitude))
+        }
+    }
+}
+
+impl<const N: usize> BitLayout for SignMag<N>
+where
+    Underlying<N>: Type,
+    Self: BitType,
+{
+    leaf_layout!(Shape::Encoded(Encoding::SignMag));
+}
@@ -1,12 +1,47 @@
-mod signed;
-mod unsigned;
+use std::{
+    fmt,
+    hash::Hash,
+    num::{IntErrorKind, ParseIntError},
+};
+
+use num_traits::{AsPrimitive, Num};
 
-use std::fmt;
+/// Implements `Binary`, `Octal`, `LowerHex` and `UpperHex` for a bit number.
+/// With the alternate flag (`{:#x}`) the number is prefixed and zero padded to
+/// the amount of digits needed to show all `N` bits.
+macro_rules! impl_radix_fmt {
+    ($ty:ident, $under:ident) => {
+        impl_radix_fmt!($ty, $under, Binary, "0b", "{:0width$b}", 1);
+        impl_radix_fmt!($ty, $under, Octal, "0o", "{:0width$o}", 3);
+        impl_radix_fmt!($ty, $under, LowerHex, "0x", "{:0width$x}", 4);
+        impl_radix_fmt!($ty, $under, UpperHex, "0x", "{:0width$X}", 4);
+    };
+    ($ty:ident, $under:ident, $trait:ident, $prefix:literal, $padded:literal, $digit_bits:literal) => {
+        impl<const N: usize> fmt::$trait for $ty<N>
+        where
+            Underlying<N>: Type,
+            <Underlying<N> as Type>::$under: fmt::$trait,
+        {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let bits = self.raw_bits();
+                if f.alternate() {
+                    let width = (N + $digit_bits - 1) / $digit_bits;
+                    f.pad_integral(true, $prefix, &format!($padded, bits, width = width))
+                } else {
+                    fmt::$trait::fmt(&bits, f)
+                }
+            }
+        }
+    };
+}
 
-use num_traits::AsPrimitive;
+mod encoding;
+mod signed;
+mod unsigned;
 
 use crate::magic::bits_to_bytes;
 
+pub use self::encoding::{Gray, SignMag, ZigZag};
 pub use self::signed::I;
 pub use self::unsigned::U;
 
@@ -17,6 +52,26 @@ fn max_with_bits<T: num_traits::PrimInt>(num_bits: usize) -> T {
         !((!T::zero()) << num_bits)
     }
 }
+/// A `u128` with the lowest `num_bits` set.
+const fn max_u128(num_bits: usize) -> u128 {
+    if num_bits >= 128 {
+        u128::MAX
+    } else {
+        !(u128::MAX << num_bits)
+    }
+}
+
+/// Truncates a `u128` to a smaller integer in a const context.
+const fn from_u128<T: Copy>(value: u128) -> T {
+    union Cast<T: Copy> {
+        wide: u128,
+        narrow: T,
+    }
+    #[cfg(target_endian = "big")]
+    let value = value << (128 - std::mem::size_of::<T>() * 8);
+    unsafe { Cast { wide: value }.narrow }
+}
+
 const fn log2(n: usize) -> usize {
     std::mem::size_of::<usize>() * 8 - n.leading_zeros() as usize
 }
@@ -25,11 +80,85 @@ pub const fn closest_pow_2(n: usize) -> usize {
     1 << (log2(n - 1))
 }
 
+/// The error returned when parsing a bit number from a string fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBitsError {
+    kind: IntErrorKind,
+}
+
+impl ParseBitsError {
+    fn new(kind: IntErrorKind) -> Self {
+        Self { kind }
+    }
+
+    /// The reason parsing failed.
+    pub fn kind(&self) -> &IntErrorKind {
+        &self.kind
+    }
+}
+
+impl From<ParseIntError> for ParseBitsError {
+    fn from(err: ParseIntError) -> Self {
+        Self::new(*err.kind())
+    }
+}
+
+impl fmt::Display for ParseBitsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            IntErrorKind::Empty => write!(f, "cannot parse integer from empty string"),
+            IntErrorKind::PosOverflow => write!(f, "number too large to fit in target bits"),
+            IntErrorKind::NegOverflow => write!(f, "number too small to fit in target bits"),
+            _ => write!(f, "invalid digit found in string"),
+        }
+    }
+}
+
+impl std::error::Error for ParseBitsError {}
+
+/// Parses an integer with an optional sign and an optional `0b`, `0o` or `0x` radix prefix.
+fn parse_radix<T: Num<FromStrRadixErr = ParseIntError>>(s: &str) -> Result<T, ParseBitsError> {
+    let (negative, digits) = match s.as_bytes().first() {
+        Some(b'-') => (true, &s[1..]),
+        Some(b'+') => (false, &s[1..]),
+        _ => (false, s),
+    };
+    let (radix, digits) = if let Some(digits) = digits.strip_prefix("0b") {
+        (2, digits)
+    } else if let Some(digits) = digits.strip_prefix("0o") {
+        (8, digits)
+    } else if let Some(digits) = digits.strip_prefix("0x") {
+        (16, digits)
+    } else {
+        (10, digits)
+    };
+    // The sign has already been consumed, don't let the underlying parser accept another one.
+    if digits.starts_with(['+', '-']) {
+        return Err(ParseBitsError::new(IntErrorKind::InvalidDigit));
+    }
+    if negative {
+        Ok(T::from_str_radix(&format!("-{}", digits), radix)?)
+    } else {
+        Ok(T::from_str_radix(digits, radix)?)
+    }
+}
+
 pub trait Type {
     type Higher: Type;
-    type U: num_traits::Unsigned + num_traits::PrimInt + fmt::Debug + Default + AsPrimitive<u32>;
+    type U: num_traits::Unsigned
+        + num_traits::PrimInt
+        + Num<FromStrRadixErr = ParseIntError>
+        + fmt::Debug
+        + Default
+        + Hash
+        + AsPrimitive<u32>;
 
-    type I: num_traits::Signed + num_traits::PrimInt + fmt::Debug + Default;
+    type I: num_traits::Signed
+        + num_traits::PrimInt
+        + Num<FromStrRadixErr = ParseIntError>
+        + fmt::Debug
+        + Default
+        + Hash;
 
     const BITS: usize;
 }
@@ -106,3 +235,42 @@ where
 {
     I::new(value)
 }
+
+/// Converts a primitive integer to `U<N>`, used for fields with `#[bitgen(bits = N)]`.
+/// Panics in debug if the value doesn't fit in N bits.
+pub fn narrow_unsigned<T, const N: usize>(value: T) -> U<N>
+where
+    T: num_traits::PrimInt + AsPrimitive<<Underlying<N> as Type>::U> + fmt::Display,
+    Underlying<N>: Type,
+{
+    #[cfg(debug_assertions)]
+    {
+        if value.to_u128().map_or(true, |value| value > max_u128(N)) {
+            panic!("The value {} does not fit in {} bits", value, N);
+        }
+    }
+    U::new(value.as_() & U::<N>::MAX.extract_underlying())
+}
+
+/// Converts a primitive integer to `I<N>`, used for fields with `#[bitgen(bits = N)]`.
+/// Panics in debug if the value doesn't fit in N bits.
+pub fn narrow_signed<T, const N: usize>(value: T) -> I<N>
+where
+    T: num_traits::PrimInt + AsPrimitive<<Underlying<N> as Type>::I> + fmt::Display,
+    Underlying<N>: Type,
+{
+    #[cfg(debug_assertions)]
+    {
+        let min = -(max_u128(N - 1) as i128) - 1;
+        let max = max_u128(N - 1) as i128;
+        if value
+            .to_i128()
+            .map_or(true, |value| value < min || value > max)
+        {
+            panic!("The value {} does not fit in {} bits", value, N);
+        }
+    }
+    let shift = <Underlying<N> as Type>::BITS - N;
+    let value: <Underlying<N> as Type>::I = value.as_();
+    I::new((value << shift) >> shift)
+}
@@ -1,9 +1,15 @@
-use std::{mem, ops};
+use std::{
+    hash::{Hash, Hasher},
+    iter::{Product, Step, Sum},
+    mem,
+    ops::{self, RangeInclusive},
+    str::FromStr,
+};
 
-use num_traits::{AsPrimitive, Num, One, PrimInt, Unsigned, Zero};
+use num_traits::{AsPrimitive, Num, NumCast, One, PrimInt, ToPrimitive, Unsigned, Zero};
 
 use super::*;
-use crate::bit_type::BitType;
+use crate::{bit_type::BitType, layout::leaf_layout, BitLayout, Shape};
 
 use super::max_with_bits;
 
@@ -16,11 +22,24 @@ impl<const N: usize> I<N>
 where
     Underlying<N>: Type,
 {
+    /// The smallest value that can be represented with N bits.
+    pub const MIN: Self = I(from_u128(1 << (N - 1)));
+    /// The largest value that can be represented with N bits.
+    pub const MAX: Self = I(from_u128(max_u128(N - 1)));
+    /// The value 0.
+    pub const ZERO: Self = I(from_u128(0));
+    /// The value 1.
+    pub const ONE: Self = I(from_u128(1));
+
+    /// An iterator over every value that can be represented with N bits, in ascending order.
+    pub fn all() -> RangeInclusive<Self> {
+        Self::MIN..=Self::MAX
+    }
+
     fn neg_bit() -> <Underlying<N> as Type>::I {
         <Underlying<N> as Type>::I::one() << (N - 1)
     }
 
-    #[cfg(debug_assertions)]
     fn fits(value: <Underlying<N> as Type>::I) -> bool {
         value
             >= -max_with_bits::<<Underlying<N> as Type>::I>(N - 1)
@@ -49,6 +68,15 @@ where
     pub fn extract_underlying(self) -> <Underlying<N> as Type>::I {
         (self.0 << (<Underlying<N> as Type>::BITS - N)) >> (<Underlying<N> as Type>::BITS - N)
     }
+
+    /// The N bit two's complement pattern of the number.
+    fn raw_bits(self) -> <Underlying<N> as Type>::I {
+        if N >= <Underlying<N> as Type>::BITS {
+            self.0
+        } else {
+            self.0 & max_with_bits(N)
+        }
+    }
 }
 
 impl<const N: usize> ops::Add<I<N>> for I<N>
@@ -367,12 +395,23 @@ where
     Underlying<N>: Type,
 {
     fn eq(&self, other: &Self) -> bool {
-        self.0.eq(&other.0)
+        // Values read from bits are sign extended while `new` masks, so compare the values like
+        // `Ord` does.
+        self.extract_underlying().eq(&other.extract_underlying())
     }
 }
 
 impl<const N: usize> Eq for I<N> where Underlying<N>: Type {}
 
+impl<const N: usize> Hash for I<N>
+where
+    Underlying<N>: Type,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.extract_underlying().hash(state)
+    }
+}
+
 impl<const N: usize> PartialOrd for I<N>
 where
     Underlying<N>: Type,
@@ -414,6 +453,126 @@ where
     }
 }
 
+impl<const N: usize> Sum for I<N>
+where
+    Underlying<N>: Type,
+{
+    fn sum<It: Iterator<Item = Self>>(iter: It) -> Self {
+        // `Add` works on the masked bits, so add the sign extended values instead.
+        I::new(iter.fold(<Underlying<N> as Type>::I::zero(), |acc, x| {
+            acc + x.extract_underlying()
+        }))
+    }
+}
+impl<'a, const N: usize> Sum<&'a I<N>> for I<N>
+where
+    Underlying<N>: Type,
+{
+    fn sum<It: Iterator<Item = &'a Self>>(iter: It) -> Self {
+        iter.copied().sum()
+    }
+}
+impl<const N: usize> Product for I<N>
+where
+    Underlying<N>: Type,
+{
+    fn product<It: Iterator<Item = Self>>(iter: It) -> Self {
+        I::new(iter.fold(<Underlying<N> as Type>::I::one(), |acc, x| {
+            acc * x.extract_underlying()
+        }))
+    }
+}
+impl<'a, const N: usize> Product<&'a I<N>> for I<N>
+where
+    Underlying<N>: Type,
+{
+    fn product<It: Iterator<Item = &'a Self>>(iter: It) -> Self {
+        iter.copied().product()
+    }
+}
+
+impl<const N: usize> Step for I<N>
+where
+    Underlying<N>: Type,
+{
+    fn steps_between(start: &Self, end: &Self) -> (usize, Option<usize>) {
+        if start > end {
+            return (0, None);
+        }
+        let steps = end
+            .extract_underlying()
+            .to_i128()
+            .zip(start.extract_underlying().to_i128())
+            .and_then(|(end, start)| end.checked_sub(start))
+            .and_then(|steps| steps.to_usize());
+        match steps {
+            Some(steps) => (steps, Some(steps)),
+            None => (usize::MAX, None),
+        }
+    }
+
+    fn forward_checked(start: Self, count: usize) -> Option<Self> {
+        let value = start
+            .extract_underlying()
+            .to_i128()?
+            .checked_add(count.to_i128()?)?;
+        if value > Self::MAX.extract_underlying().to_i128()? {
+            return None;
+        }
+        Some(I::new(NumCast::from(value)?))
+    }
+
+    fn backward_checked(start: Self, count: usize) -> Option<Self> {
+        let value = start
+            .extract_underlying()
+            .to_i128()?
+            .checked_sub(count.to_i128()?)?;
+        if value < Self::MIN.extract_underlying().to_i128()? {
+            return None;
+        }
+        Some(I::new(NumCast::from(value)?))
+    }
+}
+
+impl_radix_fmt!(I, I);
+
+impl<const N: usize> FromStr for I<N>
+where
+    Underlying<N>: Type,
+{
+    type Err = ParseBitsError;
+
+    /// Parses a decimal number, or a binary, octal or hex number prefixed with `0b`, `0o` or `0x`.
+    /// The sign goes before the prefix, i.e `-0x8`. A prefixed number without a sign is the N bit
+    /// two's complement pattern that `{:#b}`, `{:#o}` and `{:#x}` print, so `0x1f` is -1 for
+    /// `I<5>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if ["0b", "0o", "0x"]
+            .iter()
+            .any(|prefix| s.starts_with(prefix))
+        {
+            let raw = U::<N>::from_str(s)?.extract_underlying().to_i128().unwrap();
+            let value = if raw >> (N - 1) & 1 == 1 {
+                raw - (1 << N)
+            } else {
+                raw
+            };
+            return Ok(I::new(NumCast::from(value).unwrap()));
+        }
+        let value = parse_radix::<<Underlying<N> as Type>::I>(s)?;
+        if !Self::fits(value) {
+            return Err(ParseBitsError::new(
+                if value < <Underlying<N> as Type>::I::zero() {
+                    IntErrorKind::NegOverflow
+                } else {
+                    IntErrorKind::PosOverflow
+                },
+            ));
+        }
+        Ok(I::new(value))
+    }
+}
+
 impl<const A: usize, const B: usize> AsPrimitive<I<A>> for I<B>
 where
     Underlying<A>: Type,
@@ -552,6 +711,14 @@ impl_bit_type! {
     33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63,
 }
 
+impl<const N: usize> BitLayout for I<N>
+where
+    Underlying<N>: Type,
+    Self: BitType,
+{
+    leaf_layout!(Shape::Int);
+}
+
 impl BitType for I<1> {
     const BITS: usize = 1;
 
@@ -1,9 +1,17 @@
-use std::{mem, ops};
-
-use num_traits::{AsPrimitive, Num, One, PrimInt, Unsigned, Zero};
+use std::{
+    hash::{Hash, Hasher},
+    iter::{Product, Step, Sum},
+    mem,
+    ops::{self, RangeInclusive},
+    str::FromStr,
+};
+
+use num_traits::{
+    AsPrimitive, CheckedAdd, CheckedSub, Num, NumCast, One, PrimInt, ToPrimitive, Unsigned, Zero,
+};
 
 use super::*;
-use crate::bit_type::BitType;
+use crate::{bit_type::BitType, layout::leaf_layout, BitLayout, Shape};
 
 use super::max_with_bits;
 
@@ -16,6 +24,20 @@ impl<const N: usize> U<N>
 where
     Underlying<N>: Type,
 {
+    /// The smallest value that can be represented with N bits.
+    pub const MIN: Self = U(from_u128(0));
+    /// The largest value that can be represented with N bits.
+    pub const MAX: Self = U(from_u128(max_u128(N)));
+    /// The value 0.
+    pub const ZERO: Self = U(from_u128(0));
+    /// The value 1.
+    pub const ONE: Self = U(from_u128(1));
+
+    /// An iterator over every value that can be represented with N bits, in ascending order.
+    pub fn all() -> RangeInclusive<Self> {
+        Self::MIN..=Self::MAX
+    }
+
     pub fn new(value: <Underlying<N> as Type>::U) -> Self {
         #[cfg(debug_assertions)]
         {
@@ -33,6 +55,10 @@ where
     pub fn extract_underlying(self) -> <Underlying<N> as Type>::U {
         self.0
     }
+
+    fn raw_bits(self) -> <Underlying<N> as Type>::U {
+        self.0
+    }
 }
 
 impl<const N: usize> ops::Add<U<N>> for U<N>
@@ -336,6 +362,15 @@ where
 
 impl<const N: usize> Eq for U<N> where Underlying<N>: Type {}
 
+impl<const N: usize> Hash for U<N>
+where
+    Underlying<N>: Type,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
 impl<const N: usize> PartialOrd for U<N>
 where
     Underlying<N>: Type,
@@ -378,6 +413,81 @@ where
     }
 }
 
+impl<const N: usize> Sum for U<N>
+where
+    Underlying<N>: Type,
+{
+    fn sum<It: Iterator<Item = Self>>(iter: It) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+impl<'a, const N: usize> Sum<&'a U<N>> for U<N>
+where
+    Underlying<N>: Type,
+{
+    fn sum<It: Iterator<Item = &'a Self>>(iter: It) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + *x)
+    }
+}
+impl<const N: usize> Product for U<N>
+where
+    Underlying<N>: Type,
+{
+    fn product<It: Iterator<Item = Self>>(iter: It) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+impl<'a, const N: usize> Product<&'a U<N>> for U<N>
+where
+    Underlying<N>: Type,
+{
+    fn product<It: Iterator<Item = &'a Self>>(iter: It) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * *x)
+    }
+}
+
+impl<const N: usize> Step for U<N>
+where
+    Underlying<N>: Type,
+{
+    fn steps_between(start: &Self, end: &Self) -> (usize, Option<usize>) {
+        if start > end {
+            return (0, None);
+        }
+        match (end.0 - start.0).to_usize() {
+            Some(steps) => (steps, Some(steps)),
+            None => (usize::MAX, None),
+        }
+    }
+
+    fn forward_checked(start: Self, count: usize) -> Option<Self> {
+        let value = start.0.checked_add(&NumCast::from(count)?)?;
+        (value <= Self::MAX.0).then(|| U(value))
+    }
+
+    fn backward_checked(start: Self, count: usize) -> Option<Self> {
+        start.0.checked_sub(&NumCast::from(count)?).map(U)
+    }
+}
+
+impl_radix_fmt!(U, U);
+
+impl<const N: usize> FromStr for U<N>
+where
+    Underlying<N>: Type,
+{
+    type Err = ParseBitsError;
+
+    /// Parses a decimal number, or a binary, octal or hex number prefixed with `0b`, `0o` or `0x`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = parse_radix::<<Underlying<N> as Type>::U>(s)?;
+        if value > max_with_bits(N) {
+            return Err(ParseBitsError::new(IntErrorKind::PosOverflow));
+        }
+        Ok(U(value))
+    }
+}
+
 impl<const A: usize, const B: usize> AsPrimitive<U<A>> for U<B>
 where
     Underlying<A>: Type,
@@ -516,6 +626,14 @@ impl_bit_type! {
     33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63,
 }
 
+impl<const N: usize> BitLayout for U<N>
+where
+    Underlying<N>: Type,
+    Self: BitType,
+{
+    leaf_layout!(Shape::Uint);
+}
+
 impl BitType for U<1> {
     const BITS: usize = 1;
 
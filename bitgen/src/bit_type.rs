@@ -1,6 +1,9 @@
-use std::mem;
+use std::{any, mem};
 
-use crate::internal::get_byte_range;
+use crate::{
+    dynamic::read_bits, internal::get_byte_range, layout::leaf_layout, BitLayout, BitVisitor,
+    FieldLayout, Layout, Shape, VariantLayout,
+};
 
 pub trait BitType: Sized + 'static {
     const BITS: usize;
@@ -9,8 +12,37 @@ pub trait BitType: Sized + 'static {
     fn from_aligned(aligned: &Self, slice: &mut [u8], offset: usize);
 
     fn to_aligned(slice: &[u8], offset: usize) -> Self;
+
+    /// Whether the bits `to_aligned` would decode hold a value this type can write, like reserved
+    /// bits still holding their value or an enum tag that has a variant. Decoding doesn't check
+    /// it, so bits from elsewhere can be inspected first.
+    fn is_valid(_slice: &[u8], _offset: usize) -> bool {
+        true
+    }
+}
+
+/// A `BitType` laid out like `T`, a type from another crate that can't implement `BitType`
+/// itself. Derived with `#[bitgen(remote = "T")]` on a copy of `T`, and used by fields marked
+/// with `#[bitgen(with = "Self")]`.
+pub trait Remote<T>: BitType {
+    fn from_remote(remote: &T) -> Self;
+
+    fn into_remote(self) -> T;
 }
 
+/// Promises that a union deriving `BitType` holds its first field whenever it's packed, which is
+/// the field the derived `from_aligned` reads. Build the values you pack from the first field, like
+/// `Register { raw: .. }`, and write the other fields through their accessors.
+///
+/// # Safety
+/// No value of the union built from another field may be packed, with `Bit::from`, `insert` or
+/// anything else calling `from_aligned`, as that reads a field the union doesn't hold.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` needs an `unsafe impl BitUnion` to derive `BitType`",
+    note = "the derived `from_aligned` reads the first field of the union, implement `BitUnion` once every value that gets packed holds it"
+)]
+pub unsafe trait BitUnion: BitType {}
+
 macro_rules! impl_bit_tuple {
     ($($ty:literal), *) => {
         paste::paste! {
@@ -40,6 +72,48 @@ macro_rules! impl_bit_tuple {
                         )*
                     )
                 }
+
+                #[allow(unused_assignments)]
+                fn is_valid(slice: &[u8], mut offset: usize) -> bool {
+                    $(
+                        if ![<T $ty>]::is_valid(&slice[get_byte_range(offset, [<T $ty>]::BITS)], offset % 8) {
+                            return false;
+                        }
+                        offset += [<T $ty>]::BITS;
+                    )*
+                    true
+                }
+            }
+
+            impl<$([< T $ty >]: BitLayout), *> BitLayout for ($([< T $ty >],) *)
+            where
+                Self: BitType,
+            {
+                #[allow(unused_assignments)]
+                fn layout() -> Layout {
+                    let mut offset = 0;
+                    let fields = vec![
+                        $(
+                            {
+                                let field = FieldLayout::of::<[< T $ty >]>(stringify!($ty), offset);
+                                offset += [< T $ty >]::BITS;
+                                field
+                            },
+                        )*
+                    ];
+                    Layout::new::<Self>(Shape::Struct(fields))
+                }
+
+                #[allow(unused_assignments)]
+                fn visit<V: BitVisitor + ?Sized>(bytes: &[u8], mut offset: usize, visitor: &mut V) {
+                    visitor.visit_struct_begin(any::type_name::<Self>(), [$(stringify!($ty)),*].len());
+                    $(
+                        visitor.visit_field(stringify!($ty));
+                        [< T $ty >]::visit(bytes, offset, visitor);
+                        offset += [< T $ty >]::BITS;
+                    )*
+                    visitor.visit_struct_end();
+                }
             }
         }
     };
@@ -86,6 +160,35 @@ where
         }
         result
     }
+
+    fn is_valid(slice: &[u8], offset: usize) -> bool {
+        (0..N).all(|i| {
+            T::is_valid(
+                &slice[get_byte_range(offset + i * T::BITS, T::BITS)],
+                (offset + i * T::BITS) % 8,
+            )
+        })
+    }
+}
+
+impl<T: BitLayout, const N: usize> BitLayout for [T; N]
+where
+    Self: BitType,
+{
+    fn layout() -> Layout {
+        Layout::new::<Self>(Shape::Array {
+            len: N,
+            element: Box::new(T::layout()),
+        })
+    }
+
+    fn visit<V: BitVisitor + ?Sized>(bytes: &[u8], offset: usize, visitor: &mut V) {
+        visitor.visit_array(N);
+        for i in 0..N {
+            T::visit(bytes, offset + i * T::BITS, visitor);
+        }
+        visitor.visit_array_end();
+    }
 }
 
 impl BitType for () {
@@ -345,4 +448,43 @@ impl<T: BitType> BitType for Option<T> {
             Some(T::to_aligned(&slice[(offset + 1) / 8..], (offset + 1) % 8))
         }
     }
+
+    fn is_valid(slice: &[u8], offset: usize) -> bool {
+        ((slice[0] >> offset) & 1) == 0 || T::is_valid(&slice[(offset + 1) / 8..], (offset + 1) % 8)
+    }
 }
+
+impl<T: BitLayout> BitLayout for Option<T> {
+    fn layout() -> Layout {
+        Layout::new::<Self>(Shape::Enum {
+            tag_offset: 0,
+            tag_bits: 1,
+            variants: vec![
+                VariantLayout {
+                    name: "None",
+                    tag: Some(0),
+                    fields: Vec::new(),
+                },
+                VariantLayout {
+                    name: "Some",
+                    tag: Some(1),
+                    fields: vec![FieldLayout::of::<T>("0", 1)],
+                },
+            ],
+        })
+    }
+
+    fn visit<V: BitVisitor + ?Sized>(bytes: &[u8], offset: usize, visitor: &mut V) {
+        let is_some = read_bits(bytes, offset, 1) == 1;
+        visitor.visit_option(is_some);
+        if is_some {
+            T::visit(bytes, offset + 1, visitor);
+        }
+        visitor.visit_option_end();
+    }
+}
+
+leaf_layout!(Shape::Unit; ());
+leaf_layout!(Shape::Bool; bool);
+leaf_layout!(Shape::Uint; u8, u16, u32, u64);
+leaf_layout!(Shape::Int; i8, i16, i32, i64);
@@ -1,40 +1,128 @@
 pub mod accessors;
 use accessors::TupleAccess;
-use num_traits::AsPrimitive;
-use std::{marker::PhantomData, mem, ops::Range};
+use std::{
+    iter,
+    marker::PhantomData,
+    mem,
+    ops::{
+        Bound, Range, RangeBounds, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive,
+    },
+};
 use wyz::{Address, Const, Mut, Mutability};
 
 use crate::{
-    bit_num::{Type, Underlying},
     bit_type::BitType,
+    dynamic::{self, DynValue, PathError},
     magic::{bits_to_bytes, CTuple, InferEq},
-    BitContainer, U,
+    BitContainer, BitLayout, BitVisitor,
 };
 
 use self::accessors::{DynAccess, MaybeAccess};
 
 pub(crate) mod access;
 pub(crate) mod access_dyn;
+pub(crate) mod access_slice;
 mod maybe;
 mod maybe_dyn;
 mod predicate;
 
 use access_dyn::AccessDyn;
+use access_slice::AccessSlice;
+pub use access_slice::IterMut;
 use maybe::AccessMaybe;
 use maybe_dyn::AccessMaybeDyn;
 use predicate::*;
 
+pub use predicate::VariantMismatch;
+
 pub trait ChildAccess<const I: usize> {
     type Child;
     fn get_child(self) -> Self::Child;
 }
 
-pub trait ChildAccessDyn {
+pub trait ChildAccessDyn: Sized {
     type Child;
-    fn get_child_dyn(self, index: usize) -> Self::Child;
+
+    /// # Safety
+    /// `index` must be less than `get_len()`.
+    unsafe fn get_child_dyn_unchecked(self, index: usize) -> Self::Child;
+
     fn get_len(&self) -> usize;
+
+    fn get_child_dyn(self, index: usize) -> Self::Child {
+        if index >= self.get_len() {
+            panic!("index out of bounds");
+        }
+        unsafe { self.get_child_dyn_unchecked(index) }
+    }
+}
+
+pub trait ChildAccessRange {
+    type Slice;
+    /// # Panics
+    /// If `range` isn't inside the elements.
+    fn get_child_range(self, range: Range<usize>) -> Self::Slice;
+}
+
+/// Panics if `range` isn't inside `len` elements.
+pub(crate) fn check_range(range: &Range<usize>, len: usize) {
+    if range.start > range.end || range.end > len {
+        panic!("range {:?} out of bounds for length {}", range, len);
+    }
+}
+
+/// What the `[...]` accessor of `bit!` takes, an element index or a range of elements.
+pub trait DynIndex<A> {
+    type Output;
+    fn index(self, accessor: A) -> Self::Output;
 }
 
+impl<A: ChildAccessDyn> DynIndex<A> for usize {
+    type Output = A::Child;
+    fn index(self, accessor: A) -> Self::Output {
+        accessor.get_child_dyn(self)
+    }
+}
+
+macro_rules! impl_dyn_index_range {
+    ($($range:ty), *) => {
+        $(
+            impl<A: ChildAccessDyn + ChildAccessRange> DynIndex<A> for $range {
+                type Output = A::Slice;
+                fn index(self, accessor: A) -> Self::Output {
+                    let len = accessor.get_len();
+                    // A bound at `usize::MAX` can't be made exclusive, it's out of bounds anyway.
+                    let after = |bound: usize| {
+                        bound.checked_add(1).unwrap_or_else(|| {
+                            panic!("range {:?} out of bounds for length {}", self, len)
+                        })
+                    };
+                    let start = match self.start_bound() {
+                        Bound::Included(&start) => start,
+                        Bound::Excluded(&start) => after(start),
+                        Bound::Unbounded => 0,
+                    };
+                    let end = match self.end_bound() {
+                        Bound::Included(&end) => after(end),
+                        Bound::Excluded(&end) => end,
+                        Bound::Unbounded => len,
+                    };
+                    accessor.get_child_range(start..end)
+                }
+            }
+        )*
+    };
+}
+
+impl_dyn_index_range!(
+    Range<usize>,
+    RangeInclusive<usize>,
+    RangeFrom<usize>,
+    RangeTo<usize>,
+    RangeToInclusive<usize>,
+    RangeFull
+);
+
 pub trait ChildAccessMaybe<const I: usize> {
     type Child;
     fn get_child_maybe(self) -> Self::Child;
@@ -54,6 +142,7 @@ pub struct BitIter<
 > {
     accessor: A,
     elem: usize,
+    end: usize,
     _marker: PhantomData<(M, BC, T)>,
 }
 
@@ -67,16 +156,51 @@ impl<
     type Item = A::Child;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.elem < self.accessor.len() {
-            let res = Some(self.accessor.clone().get_dyn(self.elem));
+        if self.elem < self.end {
+            let res = Some(unsafe { self.accessor.clone().get_dyn_unchecked(self.elem) });
             self.elem += 1;
             res
         } else {
             None
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.elem;
+        (len, Some(len))
+    }
+}
+
+impl<
+        M: Mutability,
+        BC: BitContainer,
+        T: BitType,
+        A: Accessor<BC, T, M> + ChildAccessDyn + Clone,
+    > DoubleEndedIterator for BitIter<M, BC, T, A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.elem < self.end {
+            self.end -= 1;
+            Some(unsafe { self.accessor.clone().get_dyn_unchecked(self.end) })
+        } else {
+            None
+        }
+    }
+}
+
+impl<
+        M: Mutability,
+        BC: BitContainer,
+        T: BitType,
+        A: Accessor<BC, T, M> + ChildAccessDyn + Clone,
+    > ExactSizeIterator for BitIter<M, BC, T, A>
+{
 }
 
+/// The iterator of `Accessor::zip`, over the sub accessors of `A` and `B` together.
+pub type BitZip<M, BC, T, A, OM, OBC, OT, B> =
+    iter::Zip<BitIter<M, BC, T, A>, BitIter<OM, OBC, OT, B>>;
+
 pub const fn get_byte_range(offset: usize, size: usize) -> Range<usize> {
     if size == 0 {
         0..0
@@ -84,6 +208,13 @@ pub const fn get_byte_range(offset: usize, size: usize) -> Range<usize> {
         (offset / 8)..(offset + size - 1) / 8 + 1
     }
 }
+/// Sets the `bits` bits from `offset` to 0.
+pub fn clear_bits(bytes: &mut [u8], offset: usize, bits: usize) {
+    for bit in offset..offset + bits {
+        bytes[bit / 8] &= !(1 << (bit % 8));
+    }
+}
+
 pub trait Accessor<BC: BitContainer, T: BitType, M: Mutability>: Sized {
     type Extracted;
     type InsertResult;
@@ -112,6 +243,36 @@ pub trait Accessor<BC: BitContainer, T: BitType, M: Mutability>: Sized {
         self.get_child_dyn(index)
     }
 
+    /// Get an accessor over a range of the elements
+    fn get_range(self, range: Range<usize>) -> <Self as ChildAccessRange>::Slice
+    where
+        Self: ChildAccessRange,
+    {
+        self.get_child_range(range)
+    }
+
+    /// Get child accessor dynamically, `None` if `index` is out of bounds
+    fn try_get_dyn(self, index: usize) -> Option<<Self as ChildAccessDyn>::Child>
+    where
+        Self: ChildAccessDyn,
+    {
+        if index < self.get_len() {
+            Some(unsafe { self.get_child_dyn_unchecked(index) })
+        } else {
+            None
+        }
+    }
+
+    /// Get child accessor dynamically without checking the bounds
+    /// # Safety
+    /// `index` must be less than `len()`, otherwise the accessor reads and writes out of bounds.
+    unsafe fn get_dyn_unchecked(self, index: usize) -> <Self as ChildAccessDyn>::Child
+    where
+        Self: ChildAccessDyn,
+    {
+        self.get_child_dyn_unchecked(index)
+    }
+
     /// Length of a dynamic accessor
     fn len(&self) -> usize
     where
@@ -128,18 +289,36 @@ pub trait Accessor<BC: BitContainer, T: BitType, M: Mutability>: Sized {
         self.get_len() == 0
     }
 
-    /// Get an iterator over sub accessors
+    /// Get an iterator over sub accessors. Only for immutable accessors, as the mutable sub
+    /// accessors would all alias, use `iter_mut` or `for_each_mut` on a mutable one
     fn iter(&self) -> BitIter<M, BC, T, Self>
     where
         Self: ChildAccessDyn + Clone,
+        (M, Const): InferEq,
     {
         BitIter {
             accessor: self.clone(),
             elem: 0,
+            end: self.len(),
             _marker: PhantomData,
         }
     }
 
+    /// Get an iterator over the sub accessors of this one and `other` together, it stops at the
+    /// shorter of them
+    fn zip<OBC: BitContainer, OT: BitType, OM: Mutability, A>(
+        &self,
+        other: &A,
+    ) -> BitZip<M, BC, T, Self, OM, OBC, OT, A>
+    where
+        Self: ChildAccessDyn + Clone,
+        A: Accessor<OBC, OT, OM> + ChildAccessDyn + Clone,
+        (M, Const): InferEq,
+        (OM, Const): InferEq,
+    {
+        self.iter().zip(other.iter())
+    }
+
     type CastAccess<U: BitType, C: Mutability>;
 
     /// Get an immutable accessor
@@ -180,4 +359,86 @@ pub trait Accessor<BC: BitContainer, T: BitType, M: Mutability>: Sized {
     fn map(&self, f: impl FnMut(T) -> T) -> Self::InsertResult
     where
         (M, Mut): InferEq;
+
+    /// The result of `R` from an operation that can fail, `R` itself if it can't.
+    type Checked<R>;
+
+    /// Byte align the type and pass it to the function, then bit align and assign the new value
+    /// it returns, if any. Returns the rest of what the function returns.
+    fn modify<R>(&self, f: impl FnOnce(T) -> (Option<T>, R)) -> Self::Checked<R>
+    where
+        (M, Mut): InferEq;
+
+    /// Assign `value` and return the old value.
+    fn replace(&self, value: T) -> Self::Checked<T>
+    where
+        (M, Mut): InferEq,
+    {
+        self.modify(|old| (Some(value), old))
+    }
+
+    /// Assign the default value and return the old value.
+    fn take(&self) -> Self::Checked<T>
+    where
+        (M, Mut): InferEq,
+        T: Default,
+    {
+        self.replace(T::default())
+    }
+
+    /// Assign `new` if the value is `expected`. Returns the old value, in `Ok` if it was replaced.
+    fn compare_and_set(&self, expected: T, new: T) -> Self::Checked<Result<T, T>>
+    where
+        (M, Mut): InferEq,
+        T: PartialEq,
+    {
+        self.modify(|current| {
+            if current == expected {
+                (Some(new), Ok(current))
+            } else {
+                (None, Err(current))
+            }
+        })
+    }
+
+    /// Byte align the type, change it with the function, then bit align the result and assign it.
+    fn update(&self, f: impl FnOnce(&mut T)) -> Self::Checked<()>
+    where
+        (M, Mut): InferEq,
+    {
+        self.modify(|mut value| {
+            f(&mut value);
+            (Some(value), ())
+        })
+    }
+
+    /// Swap the values of two accessors, which can be in different containers. Nothing changes
+    /// if either of them fails.
+    fn swap<OBC: BitContainer, OM: Mutability, A: Accessor<OBC, T, OM>>(
+        &self,
+        other: &A,
+    ) -> Self::Checked<A::Checked<()>>
+    where
+        (M, Mut): InferEq,
+        (OM, Mut): InferEq,
+    {
+        self.modify(|value| {
+            let mut other_value = None;
+            let res = other.modify(|old| {
+                other_value = Some(old);
+                (Some(value), ())
+            });
+            (other_value, res)
+        })
+    }
+
+    /// Switch the type to the element `I` in place, assigning its tag and `payload`.
+    fn set_variant<const I: usize>(
+        &self,
+        payload: <T as MaybeAccess<I>>::Element,
+    ) -> Self::InsertResult
+    where
+        (M, Mut): InferEq,
+        T: MaybeAccess<I>,
+        <T as MaybeAccess<I>>::Element: BitType;
 }
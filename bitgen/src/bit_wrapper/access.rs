@@ -27,6 +27,13 @@ impl<'a, M: Mutability, BC: BitContainer, T: BitType, const OFFSET: usize>
             _marker: PhantomData,
         }
     }
+
+    /// Whether the bits hold a value `T` can write, like reserved bits that still hold their
+    /// value, see `BitType::is_valid`. `extract` doesn't check it.
+    pub fn is_valid(&self) -> bool {
+        let bytes = unsafe { &*self.bits.to_const() }.get_range(get_byte_range(OFFSET, T::BITS));
+        T::is_valid(bytes, OFFSET % 8)
+    }
 }
 
 impl<
@@ -40,16 +47,10 @@ impl<
 where
     [u8; OFFSET + <T as MaybeAccess<I>>::BIT_OFFSET]: Sized,
     <T as MaybeAccess<I>>::Element: BitType,
-    BitCheck<OFFSET, { <T as MaybeAccess<I>>::BIT_OFFSET }, { <T as MaybeAccess<I>>::EXPECTED }>:
-        BitPredicate,
 {
     type Child = AccessMaybe<
         'a,
-        BitCheck<
-            OFFSET,
-            { <T as MaybeAccess<I>>::BIT_OFFSET },
-            { <T as MaybeAccess<I>>::EXPECTED },
-        >,
+        TagCheck<T, I, OFFSET>,
         M,
         BC,
         <T as MaybeAccess<I>>::Element,
@@ -67,10 +68,7 @@ where
     T::Element: BitType,
 {
     type Child = AccessDyn<'a, M, BC, T::Element>;
-    fn get_child_dyn(self, index: usize) -> Self::Child {
-        if index >= T::MAX {
-            panic!("index out of bounds");
-        }
+    unsafe fn get_child_dyn_unchecked(self, index: usize) -> Self::Child {
         Self::Child::new(self.bits, OFFSET + T::offset(index))
     }
     fn get_len(&self) -> usize {
@@ -78,6 +76,18 @@ where
     }
 }
 
+impl<'a, M: Mutability, BC: BitContainer, T: BitType + DynAccess, const OFFSET: usize>
+    ChildAccessRange for Access<'a, M, BC, T, OFFSET>
+where
+    T::Element: BitType,
+{
+    type Slice = AccessSlice<'a, M, BC, T::Element>;
+    fn get_child_range(self, range: Range<usize>) -> Self::Slice {
+        check_range(&range, T::MAX);
+        Self::Slice::new(self.bits, OFFSET + T::offset(range.start), range.len())
+    }
+}
+
 impl<
         'a,
         M: Mutability,
@@ -139,6 +149,35 @@ where
         self.insert(f(self.extract()))
     }
 
+    type Checked<R> = R;
+
+    fn modify<R>(&self, f: impl FnOnce(T) -> (Option<T>, R)) -> Self::Checked<R>
+    where
+        (M, Mut): InferEq,
+    {
+        let (new, res) = f(self.extract());
+        if let Some(new) = new {
+            self.insert(new);
+        }
+        res
+    }
+
+    fn set_variant<const I: usize>(
+        &self,
+        payload: <T as MaybeAccess<I>>::Element,
+    ) -> Self::InsertResult
+    where
+        (M, Mut): InferEq,
+        T: MaybeAccess<I>,
+        <T as MaybeAccess<I>>::Element: BitType,
+    {
+        write_variant::<T, I, BC>(
+            unsafe { &mut *self.bits.assert_mut().to_mut() },
+            OFFSET,
+            &payload,
+        )
+    }
+
     type CastAccess<U: BitType, C: Mutability> = Access<'a, C, BC, U, OFFSET>;
 
     fn access(self) -> Self::CastAccess<T, Const> {
@@ -160,3 +199,56 @@ where
         Self::CastAccess::<U, Mut>::new(self.bits.assert_mut())
     }
 }
+
+impl<'a, BC: BitContainer, T: BitType, const OFFSET: usize> Access<'a, Mut, BC, T, OFFSET>
+where
+    [u8; bits_to_bytes(T::BITS)]: Sized,
+    [u8; mem::size_of::<T>()]: Sized,
+{
+    /// Get a cursor over the mutable accessors of the elements of the array, see
+    /// `AccessSlice::iter_mut` for why it isn't an `Iterator`
+    pub fn iter_mut(&mut self) -> IterMut<'_, BC, T::Element>
+    where
+        T: DynAccess,
+        T::Element: BitType,
+    {
+        IterMut::new(Access::<'_, Mut, BC, T, OFFSET>::new(self.bits).get_child_range(0..T::MAX))
+    }
+
+    /// Call `f` with the mutable accessor of every element of the array in turn
+    pub fn for_each_mut(&mut self, f: impl FnMut(AccessDyn<'_, Mut, BC, T::Element>))
+    where
+        T: DynAccess,
+        T::Element: BitType,
+    {
+        self.iter_mut().for_each(f)
+    }
+}
+
+impl<'a, M: Mutability, BC: BitContainer, T: BitLayout, const OFFSET: usize>
+    Access<'a, M, BC, T, OFFSET>
+{
+    /// Read the value at `path`, like `"header.flags[3]"` or `"kind?Data.len"`, through the
+    /// layout of `T`. An empty path reads the whole value.
+    pub fn get_path(&self, path: &str) -> Result<DynValue, PathError> {
+        let bytes = unsafe { &*self.bits.to_const() }.get_range(get_byte_range(OFFSET, T::BITS));
+        dynamic::get_path(bytes, &T::layout(), OFFSET % 8, path)
+    }
+
+    /// Walk every field of the value with `visitor`, without extracting it.
+    pub fn visit<V: BitVisitor + ?Sized>(&self, visitor: &mut V) {
+        let bytes = unsafe { &*self.bits.to_const() }.get_range(get_byte_range(OFFSET, T::BITS));
+        T::visit(bytes, OFFSET % 8, visitor)
+    }
+
+    /// Write `value` at `path`, see `get_path`. Structs only get the fields in `value` written,
+    /// and nothing is written when it fails.
+    pub fn set_path(&self, path: &str, value: DynValue) -> Result<(), PathError>
+    where
+        (M, Mut): InferEq,
+    {
+        let bytes = unsafe { &mut *self.bits.assert_mut().to_mut() }
+            .get_range_mut(get_byte_range(OFFSET, T::BITS));
+        dynamic::set_path(bytes, &T::layout(), OFFSET % 8, path, &value)
+    }
+}
@@ -26,6 +26,13 @@ impl<'a, M: Mutability, BC: BitContainer, T: BitType> AccessDyn<'a, M, BC, T> {
             _marker: PhantomData,
         }
     }
+
+    /// Whether the bits hold a value `T` can write, see `Access::is_valid`.
+    pub fn is_valid(&self) -> bool {
+        let bytes =
+            unsafe { &*self.bits.to_const() }.get_range(get_byte_range(self.offset, T::BITS));
+        T::is_valid(bytes, self.offset % 8)
+    }
 }
 
 impl<'a, M: Mutability, BC: BitContainer, T: BitType + DynAccess> ChildAccessDyn
@@ -34,10 +41,7 @@ where
     T::Element: BitType,
 {
     type Child = AccessDyn<'a, M, BC, T::Element>;
-    fn get_child_dyn(self, index: usize) -> Self::Child {
-        if index >= T::MAX {
-            panic!("index out of bounds");
-        }
+    unsafe fn get_child_dyn_unchecked(self, index: usize) -> Self::Child {
         Self::Child::new(self.bits, self.offset + T::offset(index))
     }
     fn get_len(&self) -> usize {
@@ -45,22 +49,30 @@ where
     }
 }
 
+impl<'a, M: Mutability, BC: BitContainer, T: BitType + DynAccess> ChildAccessRange
+    for AccessDyn<'a, M, BC, T>
+where
+    T::Element: BitType,
+{
+    type Slice = AccessSlice<'a, M, BC, T::Element>;
+    fn get_child_range(self, range: Range<usize>) -> Self::Slice {
+        check_range(&range, T::MAX);
+        Self::Slice::new(self.bits, self.offset + T::offset(range.start), range.len())
+    }
+}
+
 impl<'a, M: Mutability, BC: BitContainer, T: BitType + MaybeAccess<I>, const I: usize>
     ChildAccessMaybe<I> for AccessDyn<'a, M, BC, T>
 where
     <T as MaybeAccess<I>>::Element: BitType,
-    BitCheckDyn<{ <T as MaybeAccess<I>>::BIT_OFFSET }, { <T as MaybeAccess<I>>::EXPECTED }>:
-        BitPredicate,
 {
-    type Child = AccessMaybeDyn<
-        'a,
-        BitCheckDyn<{ <T as MaybeAccess<I>>::BIT_OFFSET }, { <T as MaybeAccess<I>>::EXPECTED }>,
-        M,
-        BC,
-        <T as MaybeAccess<I>>::Element,
-    >;
+    type Child = AccessMaybeDyn<'a, TagCheckDyn<T, I>, M, BC, <T as MaybeAccess<I>>::Element>;
     fn get_child_maybe(self) -> Self::Child {
-        Self::Child::new(self.bits, self.offset, BitCheckDyn(self.offset))
+        Self::Child::new(
+            self.bits,
+            self.offset + <T as MaybeAccess<I>>::BIT_OFFSET,
+            TagCheckDyn::new(self.offset),
+        )
     }
 }
 
@@ -111,6 +123,35 @@ where
         self.insert(f(self.extract()))
     }
 
+    type Checked<R> = R;
+
+    fn modify<R>(&self, f: impl FnOnce(T) -> (Option<T>, R)) -> Self::Checked<R>
+    where
+        (M, Mut): InferEq,
+    {
+        let (new, res) = f(self.extract());
+        if let Some(new) = new {
+            self.insert(new);
+        }
+        res
+    }
+
+    fn set_variant<const I: usize>(
+        &self,
+        payload: <T as MaybeAccess<I>>::Element,
+    ) -> Self::InsertResult
+    where
+        (M, Mut): InferEq,
+        T: MaybeAccess<I>,
+        <T as MaybeAccess<I>>::Element: BitType,
+    {
+        write_variant::<T, I, BC>(
+            unsafe { &mut *self.bits.assert_mut().to_mut() },
+            self.offset,
+            &payload,
+        )
+    }
+
     type CastAccess<U: BitType, C: Mutability> = AccessDyn<'a, C, BC, U>;
 
     fn access(self) -> Self::CastAccess<T, Const> {
@@ -132,3 +173,56 @@ where
         Self::CastAccess::<U, Mut>::new(self.bits.assert_mut(), self.offset)
     }
 }
+
+impl<'a, BC: BitContainer, T: BitType> AccessDyn<'a, Mut, BC, T>
+where
+    [u8; bits_to_bytes(T::BITS)]: Sized,
+    [u8; mem::size_of::<T>()]: Sized,
+{
+    /// Get a cursor over the mutable accessors of the elements of the array at this offset, see
+    /// `AccessSlice::iter_mut` for why it isn't an `Iterator`
+    pub fn iter_mut(&mut self) -> IterMut<'_, BC, T::Element>
+    where
+        T: DynAccess,
+        T::Element: BitType,
+    {
+        IterMut::new(
+            AccessDyn::<'_, Mut, BC, T>::new(self.bits, self.offset).get_child_range(0..T::MAX),
+        )
+    }
+
+    /// Call `f` with the mutable accessor of every element of the array at this offset in turn
+    pub fn for_each_mut(&mut self, f: impl FnMut(AccessDyn<'_, Mut, BC, T::Element>))
+    where
+        T: DynAccess,
+        T::Element: BitType,
+    {
+        self.iter_mut().for_each(f)
+    }
+}
+
+impl<'a, M: Mutability, BC: BitContainer, T: BitLayout> AccessDyn<'a, M, BC, T> {
+    /// Read the value at `path` through the layout of `T`, see `Access::get_path`.
+    pub fn get_path(&self, path: &str) -> Result<DynValue, PathError> {
+        let bytes =
+            unsafe { &*self.bits.to_const() }.get_range(get_byte_range(self.offset, T::BITS));
+        dynamic::get_path(bytes, &T::layout(), self.offset % 8, path)
+    }
+
+    /// Walk every field of the value with `visitor`, see `Access::visit`.
+    pub fn visit<V: BitVisitor + ?Sized>(&self, visitor: &mut V) {
+        let bytes =
+            unsafe { &*self.bits.to_const() }.get_range(get_byte_range(self.offset, T::BITS));
+        T::visit(bytes, self.offset % 8, visitor)
+    }
+
+    /// Write `value` at `path`, see `Access::set_path`.
+    pub fn set_path(&self, path: &str, value: DynValue) -> Result<(), PathError>
+    where
+        (M, Mut): InferEq,
+    {
+        let bytes = unsafe { &mut *self.bits.assert_mut().to_mut() }
+            .get_range_mut(get_byte_range(self.offset, T::BITS));
+        dynamic::set_path(bytes, &T::layout(), self.offset % 8, path, &value)
+    }
+}
@@ -0,0 +1,208 @@
+use crate::BitContainer;
+
+use super::*;
+
+/// An accessor over a range of elements of an array or a `BitVec`.
+pub struct AccessSlice<'a, M: Mutability, BC: BitContainer, T: BitType> {
+    bits: Address<M, BC>,
+    offset: usize,
+    len: usize,
+    _marker: PhantomData<&'a T>,
+}
+
+impl<'a, M: Mutability, BC: BitContainer, T: BitType> Clone for AccessSlice<'a, M, BC, T> {
+    fn clone(&self) -> Self {
+        Self {
+            bits: self.bits,
+            offset: self.offset,
+            len: self.len,
+            _marker: self._marker,
+        }
+    }
+}
+
+impl<'a, M: Mutability, BC: BitContainer, T: BitType> AccessSlice<'a, M, BC, T> {
+    pub(crate) fn new(bits: Address<M, BC>, offset: usize, len: usize) -> Self {
+        Self {
+            bits,
+            offset,
+            len,
+            _marker: PhantomData,
+        }
+    }
+
+    fn element_offset(&self, index: usize) -> usize {
+        self.offset + index * T::BITS
+    }
+
+    /// Amount of elements in the range
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get an iterator over the accessors of the elements. Only for immutable ranges, use
+    /// `iter_mut` on a mutable one
+    pub fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = AccessDyn<'a, M, BC, T>> + ExactSizeIterator
+    where
+        (M, Const): InferEq,
+    {
+        let slice = self.clone();
+        (0..self.len).map(move |i| AccessDyn::new(slice.bits, slice.element_offset(i)))
+    }
+
+    /// Byte align every element into `out`
+    /// # Panics
+    /// If `out` doesn't have the same length as the range.
+    pub fn extract_into(&self, out: &mut [T]) {
+        assert_eq!(
+            out.len(),
+            self.len,
+            "the slice doesn't have the length of the range"
+        );
+        let bits = unsafe { &*self.bits.to_const() };
+        for (i, element) in out.iter_mut().enumerate() {
+            let offset = self.element_offset(i);
+            *element = T::to_aligned(bits.get_range(get_byte_range(offset, T::BITS)), offset % 8);
+        }
+    }
+
+    /// Bit align `value` into every element
+    pub fn fill(&self, value: T)
+    where
+        (M, Mut): InferEq,
+    {
+        let bits = unsafe { &mut *self.bits.assert_mut().to_mut() };
+        for i in 0..self.len {
+            let offset = self.element_offset(i);
+            T::from_aligned(
+                &value,
+                bits.get_range_mut(get_byte_range(offset, T::BITS)),
+                offset % 8,
+            );
+        }
+    }
+
+    /// Bit align the elements of `src` into the range
+    /// # Panics
+    /// If `src` doesn't have the same length as the range.
+    pub fn copy_from_slice(&self, src: &[T])
+    where
+        (M, Mut): InferEq,
+    {
+        assert_eq!(
+            src.len(),
+            self.len,
+            "the slice doesn't have the length of the range"
+        );
+        let bits = unsafe { &mut *self.bits.assert_mut().to_mut() };
+        for (i, element) in src.iter().enumerate() {
+            let offset = self.element_offset(i);
+            T::from_aligned(
+                element,
+                bits.get_range_mut(get_byte_range(offset, T::BITS)),
+                offset % 8,
+            );
+        }
+    }
+}
+
+impl<'a, BC: BitContainer, T: BitType> AccessSlice<'a, Mut, BC, T> {
+    /// Get a cursor over the mutable accessors of the elements in the range. It's not an
+    /// `Iterator`: elements smaller than a byte share their bytes with their neighbours, so two
+    /// mutable accessors alive at once would alias. Each accessor borrows the cursor instead, so
+    /// loop with `while let Some(element) = iter.next()`
+    pub fn iter_mut(&mut self) -> IterMut<'_, BC, T> {
+        IterMut::new(AccessSlice::new(self.bits, self.offset, self.len))
+    }
+
+    /// Call `f` with the mutable accessor of every element in the range in turn, see `iter_mut`
+    pub fn for_each_mut(&mut self, f: impl FnMut(AccessDyn<'_, Mut, BC, T>)) {
+        self.iter_mut().for_each(f)
+    }
+}
+
+/// A cursor over the mutable accessors of a range of elements, from `iter_mut`. Only one accessor
+/// it gives exists at a time, as it borrows the cursor.
+pub struct IterMut<'a, BC: BitContainer, T: BitType> {
+    slice: AccessSlice<'a, Mut, BC, T>,
+    front: usize,
+    back: usize,
+}
+
+#[allow(clippy::should_implement_trait)]
+impl<'a, BC: BitContainer, T: BitType> IterMut<'a, BC, T> {
+    pub(crate) fn new(slice: AccessSlice<'a, Mut, BC, T>) -> Self {
+        let back = slice.len;
+        Self {
+            slice,
+            front: 0,
+            back,
+        }
+    }
+
+    /// The accessor of the next element from the front
+    pub fn next(&mut self) -> Option<AccessDyn<'_, Mut, BC, T>> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        Some(AccessDyn::new(
+            self.slice.bits,
+            self.slice.element_offset(self.front - 1),
+        ))
+    }
+
+    /// The accessor of the next element from the back
+    pub fn next_back(&mut self) -> Option<AccessDyn<'_, Mut, BC, T>> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(AccessDyn::new(
+            self.slice.bits,
+            self.slice.element_offset(self.back),
+        ))
+    }
+
+    /// Amount of elements left
+    pub fn len(&self) -> usize {
+        self.back - self.front
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.front == self.back
+    }
+
+    /// Call `f` with the accessor of every element left in turn
+    pub fn for_each(mut self, mut f: impl FnMut(AccessDyn<'_, Mut, BC, T>)) {
+        while let Some(element) = self.next() {
+            f(element);
+        }
+    }
+}
+
+impl<'a, M: Mutability, BC: BitContainer, T: BitType> ChildAccessDyn for AccessSlice<'a, M, BC, T> {
+    type Child = AccessDyn<'a, M, BC, T>;
+    unsafe fn get_child_dyn_unchecked(self, index: usize) -> Self::Child {
+        Self::Child::new(self.bits, self.element_offset(index))
+    }
+    fn get_len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a, M: Mutability, BC: BitContainer, T: BitType> ChildAccessRange
+    for AccessSlice<'a, M, BC, T>
+{
+    type Slice = Self;
+    fn get_child_range(self, range: Range<usize>) -> Self::Slice {
+        check_range(&range, self.len);
+        Self::new(self.bits, self.element_offset(range.start), range.len())
+    }
+}
@@ -16,8 +16,19 @@ pub trait DynAccess {
 
 pub trait MaybeAccess<const I: usize> {
     type Element;
+    /// Offset of the element.
     const BIT_OFFSET: usize;
+    /// Offset of the tag, the tag comes before the element by default.
+    const TAG_OFFSET: usize = 0;
+    /// Number of bits in the tag.
+    const TAG_BITS: usize = Self::BIT_OFFSET;
+    /// The tag when the element is present.
     const EXPECTED: u32;
+
+    /// Returns true if `tag` selects the element, by default only `EXPECTED` does.
+    fn matches(tag: u32) -> bool {
+        tag == Self::EXPECTED
+    }
 }
 
 // fnv1a_hash_str_64("None") -> 7393530455478880603
@@ -59,19 +59,10 @@ impl<
 where
     [u8; OFFSET + <T as MaybeAccess<I>>::BIT_OFFSET]: Sized,
     <T as MaybeAccess<I>>::Element: BitType,
-    BitCheck<OFFSET, { <T as MaybeAccess<I>>::BIT_OFFSET }, { <T as MaybeAccess<I>>::EXPECTED }>:
-        BitPredicate,
 {
     type Child = AccessMaybe<
         'a,
-        PredicateAnd<
-            BitCheck<
-                OFFSET,
-                { <T as MaybeAccess<I>>::BIT_OFFSET },
-                { <T as MaybeAccess<I>>::EXPECTED },
-            >,
-            P,
-        >,
+        PredicateAnd<TagCheck<T, I, OFFSET>, P>,
         M,
         BC,
         <T as MaybeAccess<I>>::Element,
@@ -95,10 +86,7 @@ where
     T::Element: BitType,
 {
     type Child = AccessMaybeDyn<'a, P, M, BC, T::Element>;
-    fn get_child_dyn(self, index: usize) -> Self::Child {
-        if index >= T::MAX {
-            panic!("index out of bounds");
-        }
+    unsafe fn get_child_dyn_unchecked(self, index: usize) -> Self::Child {
         Self::Child::new(self.bits, OFFSET + T::offset(index), P::default())
     }
     fn get_len(&self) -> usize {
@@ -147,7 +135,7 @@ where
 {
     type Extracted = Option<T>;
 
-    type InsertResult = Result<(), ()>;
+    type InsertResult = Result<(), VariantMismatch>;
 
     fn extract(&self) -> Self::Extracted {
         if P::default().is_true(unsafe { &*self.bits.to_const() }.get_full()) {
@@ -164,39 +152,73 @@ where
     where
         (M, Mut): InferEq,
     {
-        if P::default().is_true(unsafe { &*self.bits.to_const() }.get_full()) {
-            T::from_aligned(
-                &aligned,
-                unsafe { &mut *self.bits.assert_mut().to_mut() }
-                    .get_range_mut(get_byte_range(OFFSET, T::BITS)),
-                OFFSET % 8,
-            );
-            Ok(())
-        } else {
-            Ok(())
-        }
+        P::default().check(unsafe { &*self.bits.to_const() }.get_full())?;
+        T::from_aligned(
+            &aligned,
+            unsafe { &mut *self.bits.assert_mut().to_mut() }
+                .get_range_mut(get_byte_range(OFFSET, T::BITS)),
+            OFFSET % 8,
+        );
+        Ok(())
     }
 
     fn map(&self, mut f: impl FnMut(T) -> T) -> Self::InsertResult
     where
         (M, Mut): InferEq,
     {
-        if P::default().is_true(unsafe { &*self.bits.to_const() }.get_full()) {
-            let extracted = T::to_aligned(
-                unsafe { &*self.bits.to_const() }.get_range(get_byte_range(OFFSET, T::BITS)),
-                OFFSET % 8,
-            );
-            let mapped = f(extracted);
+        P::default().check(unsafe { &*self.bits.to_const() }.get_full())?;
+        let extracted = T::to_aligned(
+            unsafe { &*self.bits.to_const() }.get_range(get_byte_range(OFFSET, T::BITS)),
+            OFFSET % 8,
+        );
+        let mapped = f(extracted);
+        T::from_aligned(
+            &mapped,
+            unsafe { &mut *self.bits.assert_mut().to_mut() }
+                .get_range_mut(get_byte_range(OFFSET, T::BITS)),
+            OFFSET % 8,
+        );
+        Ok(())
+    }
+
+    type Checked<R> = Result<R, VariantMismatch>;
+
+    fn modify<R>(&self, f: impl FnOnce(T) -> (Option<T>, R)) -> Self::Checked<R>
+    where
+        (M, Mut): InferEq,
+    {
+        P::default().check(unsafe { &*self.bits.to_const() }.get_full())?;
+        let (new, res) = f(T::to_aligned(
+            unsafe { &*self.bits.to_const() }.get_range(get_byte_range(OFFSET, T::BITS)),
+            OFFSET % 8,
+        ));
+        if let Some(new) = new {
             T::from_aligned(
-                &mapped,
+                &new,
                 unsafe { &mut *self.bits.assert_mut().to_mut() }
                     .get_range_mut(get_byte_range(OFFSET, T::BITS)),
                 OFFSET % 8,
             );
-            Ok(())
-        } else {
-            Err(())
         }
+        Ok(res)
+    }
+
+    fn set_variant<const I: usize>(
+        &self,
+        payload: <T as MaybeAccess<I>>::Element,
+    ) -> Self::InsertResult
+    where
+        (M, Mut): InferEq,
+        T: MaybeAccess<I>,
+        <T as MaybeAccess<I>>::Element: BitType,
+    {
+        P::default().check(unsafe { &*self.bits.to_const() }.get_full())?;
+        write_variant::<T, I, BC>(
+            unsafe { &mut *self.bits.assert_mut().to_mut() },
+            OFFSET,
+            &payload,
+        );
+        Ok(())
     }
 
     type CastAccess<U: BitType, C: Mutability> = AccessMaybe<'a, P, C, BC, U, OFFSET>;
@@ -220,3 +242,22 @@ where
         Self::CastAccess::<U, Mut>::new(self.bits.assert_mut())
     }
 }
+
+impl<
+        'a,
+        P: BitPredicate + Default + SetVariant<Element = T>,
+        M: Mutability,
+        BC: BitContainer,
+        T: BitType,
+        const OFFSET: usize,
+    > AccessMaybe<'a, P, M, BC, T, OFFSET>
+{
+    /// Insert into the variant, switching the enum to it first when it has another variant.
+    /// Only fails when a variant further up the path doesn't match.
+    pub fn insert_or_set_variant(&self, aligned: T) -> Result<(), VariantMismatch>
+    where
+        (M, Mut): InferEq,
+    {
+        P::default().set_variant(unsafe { &mut *self.bits.assert_mut().to_mut() }, &aligned)
+    }
+}
@@ -45,15 +45,10 @@ impl<
     > ChildAccessMaybe<I> for AccessMaybeDyn<'a, P, M, BC, T>
 where
     <T as MaybeAccess<I>>::Element: BitType,
-    BitCheckDyn<{ <T as MaybeAccess<I>>::BIT_OFFSET }, { <T as MaybeAccess<I>>::EXPECTED }>:
-        BitPredicate,
 {
     type Child = AccessMaybeDyn<
         'a,
-        PredicateAnd<
-            BitCheckDyn<{ <T as MaybeAccess<I>>::BIT_OFFSET }, { <T as MaybeAccess<I>>::EXPECTED }>,
-            P,
-        >,
+        PredicateAnd<TagCheckDyn<T, I>, P>,
         M,
         BC,
         <T as MaybeAccess<I>>::Element,
@@ -63,7 +58,7 @@ where
         Self::Child::new(
             self.bits,
             self.offset + <T as MaybeAccess<I>>::BIT_OFFSET,
-            PredicateAnd(BitCheckDyn(self.offset), self.predicate),
+            PredicateAnd(TagCheckDyn::new(self.offset), self.predicate),
         )
     }
 }
@@ -74,10 +69,7 @@ where
     T::Element: BitType,
 {
     type Child = AccessMaybeDyn<'a, P, M, BC, T::Element>;
-    fn get_child_dyn(self, index: usize) -> Self::Child {
-        if index >= T::MAX {
-            panic!("index out of bounds");
-        }
+    unsafe fn get_child_dyn_unchecked(self, index: usize) -> Self::Child {
         Self::Child::new(self.bits, self.offset + T::offset(index), self.predicate)
     }
     fn get_len(&self) -> usize {
@@ -114,7 +106,7 @@ where
 {
     type Extracted = Option<T>;
 
-    type InsertResult = Result<(), ()>;
+    type InsertResult = Result<(), VariantMismatch>;
 
     fn extract(&self) -> Self::Extracted {
         if self
@@ -134,45 +126,77 @@ where
     where
         (M, Mut): InferEq,
     {
-        if self
-            .predicate
-            .is_true(unsafe { &*self.bits.to_const() }.get_full())
-        {
-            T::from_aligned(
-                &aligned,
-                unsafe { &mut *self.bits.assert_mut().to_mut() }
-                    .get_range_mut(get_byte_range(self.offset, T::BITS)),
-                self.offset % 8,
-            );
-            Ok(())
-        } else {
-            Ok(())
-        }
+        self.predicate
+            .check(unsafe { &*self.bits.to_const() }.get_full())?;
+        T::from_aligned(
+            &aligned,
+            unsafe { &mut *self.bits.assert_mut().to_mut() }
+                .get_range_mut(get_byte_range(self.offset, T::BITS)),
+            self.offset % 8,
+        );
+        Ok(())
     }
 
     fn map(&self, mut f: impl FnMut(T) -> T) -> Self::InsertResult
     where
         (M, Mut): InferEq,
     {
-        if self
-            .predicate
-            .is_true(unsafe { &*self.bits.to_const() }.get_full())
-        {
-            let extracted = T::to_aligned(
-                unsafe { &*self.bits.to_const() }.get_range(get_byte_range(self.offset, T::BITS)),
-                self.offset % 8,
-            );
-            let mapped = f(extracted);
+        self.predicate
+            .check(unsafe { &*self.bits.to_const() }.get_full())?;
+        let extracted = T::to_aligned(
+            unsafe { &*self.bits.to_const() }.get_range(get_byte_range(self.offset, T::BITS)),
+            self.offset % 8,
+        );
+        let mapped = f(extracted);
+        T::from_aligned(
+            &mapped,
+            unsafe { &mut *self.bits.assert_mut().to_mut() }
+                .get_range_mut(get_byte_range(self.offset, T::BITS)),
+            self.offset % 8,
+        );
+        Ok(())
+    }
+
+    type Checked<R> = Result<R, VariantMismatch>;
+
+    fn modify<R>(&self, f: impl FnOnce(T) -> (Option<T>, R)) -> Self::Checked<R>
+    where
+        (M, Mut): InferEq,
+    {
+        self.predicate
+            .check(unsafe { &*self.bits.to_const() }.get_full())?;
+        let (new, res) = f(T::to_aligned(
+            unsafe { &*self.bits.to_const() }.get_range(get_byte_range(self.offset, T::BITS)),
+            self.offset % 8,
+        ));
+        if let Some(new) = new {
             T::from_aligned(
-                &mapped,
+                &new,
                 unsafe { &mut *self.bits.assert_mut().to_mut() }
                     .get_range_mut(get_byte_range(self.offset, T::BITS)),
                 self.offset % 8,
             );
-            Ok(())
-        } else {
-            Err(())
         }
+        Ok(res)
+    }
+
+    fn set_variant<const I: usize>(
+        &self,
+        payload: <T as MaybeAccess<I>>::Element,
+    ) -> Self::InsertResult
+    where
+        (M, Mut): InferEq,
+        T: MaybeAccess<I>,
+        <T as MaybeAccess<I>>::Element: BitType,
+    {
+        self.predicate
+            .check(unsafe { &*self.bits.to_const() }.get_full())?;
+        write_variant::<T, I, BC>(
+            unsafe { &mut *self.bits.assert_mut().to_mut() },
+            self.offset,
+            &payload,
+        );
+        Ok(())
     }
 
     type CastAccess<U: BitType, C: Mutability> = AccessMaybeDyn<'a, P, C, BC, U>;
@@ -196,3 +220,22 @@ where
         Self::CastAccess::<U, Mut>::new(self.bits.assert_mut(), self.offset, self.predicate)
     }
 }
+
+impl<
+        'a,
+        P: BitPredicate + SetVariant<Element = T>,
+        M: Mutability,
+        BC: BitContainer,
+        T: BitType,
+    > AccessMaybeDyn<'a, P, M, BC, T>
+{
+    /// Insert into the variant, switching the enum to it first when it has another variant.
+    /// Only fails when a variant further up the path doesn't match.
+    pub fn insert_or_set_variant(&self, aligned: T) -> Result<(), VariantMismatch>
+    where
+        (M, Mut): InferEq,
+    {
+        self.predicate
+            .set_variant(unsafe { &mut *self.bits.assert_mut().to_mut() }, &aligned)
+    }
+}
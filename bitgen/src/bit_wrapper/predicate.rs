@@ -1,53 +1,212 @@
+use std::fmt;
+
+use crate::BitContainer;
+
 use super::*;
 
+/// The error returned when a maybe accessor is used while its path has another variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VariantMismatch {
+    /// The tag of the variant in the path, for `other` variants the first tag they take.
+    pub expected: u32,
+    /// The tag in the bits.
+    pub found: u32,
+}
+
+impl fmt::Display for VariantMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected the variant with tag {}, found tag {}",
+            self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for VariantMismatch {}
+
 pub trait BitPredicate: Clone {
-    fn is_true(&self, slice: &[u8]) -> bool;
+    /// Checks every tag, from the outermost one, and returns the first that doesn't match.
+    fn check(&self, slice: &[u8]) -> Result<(), VariantMismatch>;
+
+    fn is_true(&self, slice: &[u8]) -> bool {
+        self.check(slice).is_ok()
+    }
+}
+
+/// A predicate ending with the tag check of a variant, which can switch to that variant.
+pub trait SetVariant: BitPredicate {
+    /// The element of the variant.
+    type Element;
+
+    /// Checks the tags before the last one and switches the last one to its variant.
+    fn set_variant<BC: BitContainer>(
+        &self,
+        bits: &mut BC,
+        payload: &Self::Element,
+    ) -> Result<(), VariantMismatch>;
 }
+
+/// `A` is checked inside of `B`, `B` is the path to it.
 #[derive(Default, Clone)]
 pub struct PredicateAnd<A: BitPredicate, B: BitPredicate>(pub A, pub B);
 
 impl<A: BitPredicate, B: BitPredicate> BitPredicate for PredicateAnd<A, B> {
-    fn is_true(&self, slice: &[u8]) -> bool {
-        self.0.is_true(slice) && self.1.is_true(slice)
+    fn check(&self, slice: &[u8]) -> Result<(), VariantMismatch> {
+        self.1.check(slice)?;
+        self.0.check(slice)
     }
 }
 
-#[derive(Default, Clone)]
-pub struct BitCheck<const OFFSET: usize, const NUM_BITS: usize, const BITS: u32>;
+impl<A: SetVariant, B: BitPredicate> SetVariant for PredicateAnd<A, B> {
+    type Element = A::Element;
 
-#[derive(Clone)]
-pub struct BitCheckDyn<const NUM_BITS: usize, const BITS: u32>(pub usize);
+    fn set_variant<BC: BitContainer>(
+        &self,
+        bits: &mut BC,
+        payload: &Self::Element,
+    ) -> Result<(), VariantMismatch> {
+        self.1.check(bits.get_full())?;
+        self.0.set_variant(bits, payload)
+    }
+}
 
-impl<const OFFSET: usize, const NUM_BITS: usize, const BITS: u32> BitPredicate
-    for BitCheck<OFFSET, NUM_BITS, BITS>
-where
-    Underlying<NUM_BITS>: Type,
-    U<NUM_BITS>: BitType,
+/// Reads a tag of `num_bits` bits at `offset`.
+fn read_tag(slice: &[u8], offset: usize, num_bits: usize) -> u32 {
+    (0..num_bits).fold(0, |tag, i| {
+        let bit = (slice[(offset + i) / 8] >> ((offset + i) % 8)) & 1;
+        tag | (bit as u32) << i
+    })
+}
+
+/// Writes `tag` in `num_bits` bits at `offset`.
+fn write_tag(slice: &mut [u8], offset: usize, num_bits: usize, tag: u32) {
+    for i in 0..num_bits {
+        let mask = 1 << ((offset + i) % 8);
+        if (tag >> i) & 1 == 1 {
+            slice[(offset + i) / 8] |= mask;
+        } else {
+            slice[(offset + i) / 8] &= !mask;
+        }
+    }
+}
+
+/// Switches the `T` placed at `offset` to the element `I`, writing its tag and `payload`.
+pub(super) fn write_variant<T: MaybeAccess<I>, const I: usize, BC: BitContainer>(
+    bits: &mut BC,
+    offset: usize,
+    payload: &T::Element,
+) where
+    T::Element: BitType,
 {
-    fn is_true(&self, slice: &[u8]) -> bool {
-        U::<NUM_BITS>::to_aligned(&slice[get_byte_range(OFFSET, NUM_BITS)], OFFSET % 8).as_()
-            == BITS
+    let tag_offset = offset + T::TAG_OFFSET;
+    write_tag(
+        bits.get_range_mut(get_byte_range(tag_offset, T::TAG_BITS)),
+        tag_offset % 8,
+        T::TAG_BITS,
+        T::EXPECTED,
+    );
+    // The payload goes after the tag, so it wins where they overlap like in `other` variants.
+    let payload_offset = offset + T::BIT_OFFSET;
+    T::Element::from_aligned(
+        payload,
+        bits.get_range_mut(get_byte_range(payload_offset, T::Element::BITS)),
+        payload_offset % 8,
+    );
+    debug_assert!(
+        T::matches(read_tag(bits.get_full(), tag_offset, T::TAG_BITS)),
+        "the payload is the tag of another variant"
+    );
+}
+
+/// True when the tag of `T`, placed at `OFFSET`, selects the element `I`.
+pub struct TagCheck<T, const I: usize, const OFFSET: usize>(PhantomData<T>);
+
+/// Same as `TagCheck` but with the offset of `T` known at runtime.
+pub struct TagCheckDyn<T, const I: usize>(pub usize, PhantomData<T>);
+
+impl<T, const I: usize, const OFFSET: usize> Default for TagCheck<T, I, OFFSET> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T, const I: usize, const OFFSET: usize> Clone for TagCheck<T, I, OFFSET> {
+    fn clone(&self) -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T, const I: usize> TagCheckDyn<T, I> {
+    pub fn new(offset: usize) -> Self {
+        Self(offset, PhantomData)
+    }
+}
+
+impl<T, const I: usize> Clone for TagCheckDyn<T, I> {
+    fn clone(&self) -> Self {
+        Self(self.0, PhantomData)
     }
 }
 
-impl<const N: usize> BitPredicate for BitCheck<N, 0, 0> {
-    fn is_true(&self, _: &[u8]) -> bool {
-        true
+/// Checks the tag of `T` at `offset`.
+fn check_tag<T: MaybeAccess<I>, const I: usize>(
+    slice: &[u8],
+    offset: usize,
+) -> Result<(), VariantMismatch> {
+    let found = read_tag(slice, offset + T::TAG_OFFSET, T::TAG_BITS);
+    if T::matches(found) {
+        Ok(())
+    } else {
+        Err(VariantMismatch {
+            expected: T::EXPECTED,
+            found,
+        })
     }
 }
-impl BitPredicate for BitCheckDyn<0, 0> {
-    fn is_true(&self, _: &[u8]) -> bool {
-        true
+
+impl<T: MaybeAccess<I>, const I: usize, const OFFSET: usize> BitPredicate
+    for TagCheck<T, I, OFFSET>
+{
+    fn check(&self, slice: &[u8]) -> Result<(), VariantMismatch> {
+        check_tag::<T, I>(slice, OFFSET)
     }
 }
 
-impl<const NUM_BITS: usize, const BITS: u32> BitPredicate for BitCheckDyn<NUM_BITS, BITS>
+impl<T: MaybeAccess<I>, const I: usize> BitPredicate for TagCheckDyn<T, I> {
+    fn check(&self, slice: &[u8]) -> Result<(), VariantMismatch> {
+        check_tag::<T, I>(slice, self.0)
+    }
+}
+
+impl<T: MaybeAccess<I>, const I: usize, const OFFSET: usize> SetVariant for TagCheck<T, I, OFFSET>
 where
-    Underlying<NUM_BITS>: Type,
-    U<NUM_BITS>: BitType,
+    T::Element: BitType,
 {
-    fn is_true(&self, slice: &[u8]) -> bool {
-        U::<NUM_BITS>::to_aligned(&slice[get_byte_range(self.0, NUM_BITS)], self.0 % 8).as_()
-            == BITS
+    type Element = T::Element;
+
+    fn set_variant<BC: BitContainer>(
+        &self,
+        bits: &mut BC,
+        payload: &Self::Element,
+    ) -> Result<(), VariantMismatch> {
+        write_variant::<T, I, BC>(bits, OFFSET, payload);
+        Ok(())
+    }
+}
+
+impl<T: MaybeAccess<I>, const I: usize> SetVariant for TagCheckDyn<T, I>
+where
+    T::Element: BitType,
+{
+    type Element = T::Element;
+
+    fn set_variant<BC: BitContainer>(
+        &self,
+        bits: &mut BC,
+        payload: &Self::Element,
+    ) -> Result<(), VariantMismatch> {
+        write_variant::<T, I, BC>(bits, self.0, payload);
+        Ok(())
     }
 }
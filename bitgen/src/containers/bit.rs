@@ -4,8 +4,9 @@ use wyz::{Address, Const, Mut};
 
 use crate::{
     bit_wrapper::access::Access,
+    dynamic,
     magic::{bits_to_bytes, CTuple, InferEq},
-    BitContainer, BitType,
+    BitContainer, BitLayout, BitType, BitVisitor, DynValue, PathError, Shape,
 };
 
 pub struct Bit<T: BitType>
@@ -20,6 +21,17 @@ impl<T: BitType> Bit<T>
 where
     [u8; bits_to_bytes(T::BITS)]: Sized,
 {
+    /// Show the bytes instead of the decoded value, see `RawBits`
+    pub fn raw(&self) -> RawBits<'_> {
+        RawBits(&self.mem)
+    }
+
+    /// Whether the bits hold a value `T` can write, like reserved bits that still hold their
+    /// value, see `BitType::is_valid`.
+    pub fn is_valid(&self) -> bool {
+        T::is_valid(&self.mem, 0)
+    }
+
     /// Get an immutable accessor
     pub fn access(&self) -> Access<'_, Const, Self, T, 0> {
         Access::new(Address::from(self))
@@ -51,13 +63,38 @@ where
     }
 }
 
-impl<T: BitType> fmt::Debug for Bit<T>
+impl<T: BitLayout> Bit<T>
 where
     [u8; bits_to_bytes(T::BITS)]: Sized,
 {
+    /// Read the value at `path`, like `"header.flags[3]"`, without knowing `T`. Fields are
+    /// `.field`, array elements `[i]` and enum variants `?Variant`, which fails if the bits hold
+    /// another variant.
+    pub fn get_path(&self, path: &str) -> Result<DynValue, PathError> {
+        self.access().get_path(path)
+    }
+
+    /// Write `value` at `path`, see `get_path`. Structs only get the fields in `value` written,
+    /// and writing a `DynValue::Variant` to an enum switches it to that variant, zeroing the
+    /// fields it doesn't give. Nothing is written when it fails.
+    pub fn set_path(&mut self, path: &str, value: DynValue) -> Result<(), PathError> {
+        self.access_mut().set_path(path, value)
+    }
+
+    /// Walk every field of the value with `visitor`, straight from the packed bits.
+    pub fn visit<V: BitVisitor + ?Sized>(&self, visitor: &mut V) {
+        T::visit(&self.mem, 0, visitor)
+    }
+}
+
+/// Shows the bytes of a `Bit` like `Bit[00101100_00000001]`, the first byte first. Made with
+/// `Bit::raw`.
+pub struct RawBits<'a>(&'a [u8]);
+
+impl fmt::Display for RawBits<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Bit[")?;
-        for (i, byte) in self.mem.iter().enumerate() {
+        for (i, byte) in self.0.iter().enumerate() {
             if i > 0 {
                 write!(f, "_")?;
             }
@@ -67,6 +104,42 @@ where
     }
 }
 
+impl fmt::Debug for RawBits<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Shows the decoded value like `Bit { flags: [true, false], kind: Data(5) }`. With `{:#?}` every
+/// number, `bool` and enum tag gets a line with its bit range, its bits and its value. An opaque
+/// type, see `BitLayout`, is shown like `Bit::raw`.
+impl<T: BitLayout> fmt::Debug for Bit<T>
+where
+    [u8; bits_to_bytes(T::BITS)]: Sized,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let layout = T::layout();
+        if layout.shape == Shape::Opaque {
+            return write!(f, "{}", self.raw());
+        }
+        if f.alternate() {
+            return dynamic::write_diagram(f, &self.mem, &layout, 0);
+        }
+        match self.get_path("") {
+            Ok(value @ DynValue::Struct(_)) => {
+                let value = value.to_string();
+                if value.starts_with('(') {
+                    write!(f, "Bit{}", value)
+                } else {
+                    write!(f, "Bit {}", value)
+                }
+            }
+            Ok(value) => write!(f, "Bit({})", value),
+            Err(err) => write!(f, "{} ({})", self.raw(), err),
+        }
+    }
+}
+
 impl<T: BitType> Default for Bit<T>
 where
     [u8; bits_to_bytes(T::BITS)]: Sized,
@@ -1,9 +1,9 @@
-use std::{marker::PhantomData, ptr};
+use std::{fmt, iter::FromIterator, marker::PhantomData, ops::Range, ptr};
 
 use wyz::{Address, Const, Mut};
 
 use crate::{
-    bit_wrapper::{access_dyn::AccessDyn, get_byte_range},
+    bit_wrapper::{access_dyn::AccessDyn, access_slice::AccessSlice, check_range, get_byte_range},
     magic::bits_to_bytes,
     BitContainer, BitType,
 };
@@ -90,6 +90,22 @@ where
     pub fn access_mut(&mut self, index: usize) -> AccessDyn<'_, Mut, Self, T> {
         AccessDyn::new(Address::from(self), index * T::BITS)
     }
+
+    /// Get an immutable accessor over the elements in `range`
+    /// # Panics
+    /// If `range` isn't inside the vector.
+    pub fn access_range(&self, range: Range<usize>) -> AccessSlice<'_, Const, Self, T> {
+        check_range(&range, self.len);
+        AccessSlice::new(Address::from(self), range.start * T::BITS, range.len())
+    }
+
+    /// Get a mutable accessor over the elements in `range`
+    /// # Panics
+    /// If `range` isn't inside the vector.
+    pub fn access_range_mut(&mut self, range: Range<usize>) -> AccessSlice<'_, Mut, Self, T> {
+        check_range(&range, self.len);
+        AccessSlice::new(Address::from(self), range.start * T::BITS, range.len())
+    }
 }
 
 impl<T: BitType> Default for BitVec<T>
@@ -101,6 +117,30 @@ where
     }
 }
 
+impl<T: BitType> FromIterator<T> for BitVec<T>
+where
+    [u8; bits_to_bytes(T::BITS)]: Sized,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        for t in iter {
+            vec.push(t);
+        }
+        vec
+    }
+}
+
+impl<T: BitType + fmt::Debug> fmt::Debug for BitVec<T>
+where
+    [u8; bits_to_bytes(T::BITS)]: Sized,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries((0..self.len).filter_map(|i| self.get(i)))
+            .finish()
+    }
+}
+
 impl<T: BitType> BitContainer for BitVec<T>
 where
     [u8; bits_to_bytes(T::BITS)]: Sized,
@@ -4,7 +4,7 @@ mod raw_vec;
 
 use std::ops::Range;
 
-pub use bit::Bit;
+pub use bit::{Bit, RawBits};
 pub use bit_vec::BitVec;
 
 pub trait BitContainer {
@@ -0,0 +1,863 @@
+use std::{fmt, ops::Range};
+
+use crate::{Encoding, FieldLayout, Layout, Shape, VariantLayout};
+
+/// A value read from the bits through its `Layout`, for tools that don't know the type at
+/// compile time. Encoded numbers are decoded, a `Gray` is a `Uint` and a `ZigZag` or `SignMag` is
+/// an `Int`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DynValue {
+    Unit,
+    Bool(bool),
+    Uint {
+        bits: usize,
+        value: u128,
+    },
+    Int {
+        bits: usize,
+        value: i128,
+    },
+    Array(Vec<DynValue>),
+    /// A struct, tuple or union with its fields in order, every view of a union is read.
+    Struct(Vec<(String, DynValue)>),
+    /// An enum variant with its fields, the `#[bitgen(other)]` variant has its tag as field `0`.
+    Variant {
+        name: String,
+        fields: Vec<(String, DynValue)>,
+    },
+    /// The bits of a type without a layout of its own, see `Shape::Opaque`, the lowest bit first.
+    Opaque {
+        bits: usize,
+        bytes: Vec<u8>,
+    },
+}
+
+impl DynValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            DynValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_uint(&self) -> Option<u128> {
+        match self {
+            DynValue::Uint { value, .. } => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i128> {
+        match self {
+            DynValue::Int { value, .. } => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The field called `name` of a struct or variant.
+    pub fn field(&self, name: &str) -> Option<&DynValue> {
+        match self {
+            DynValue::Struct(fields) | DynValue::Variant { fields, .. } => fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DynValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn is_tuple(fields: &[(String, DynValue)]) -> bool {
+            fields.iter().all(|(name, _)| name.parse::<usize>().is_ok())
+        }
+        /// Writes tuple fields as `(a, b)` and named ones as `{ a: 1, b: 2 }`.
+        fn fields(f: &mut fmt::Formatter, fields: &[(String, DynValue)]) -> fmt::Result {
+            let tuple = is_tuple(fields);
+            write!(f, "{}", if tuple { "(" } else { "{ " })?;
+            for (i, (name, value)) in fields.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                if !tuple {
+                    write!(f, "{}: ", name)?;
+                }
+                write!(f, "{}", value)?;
+            }
+            write!(f, "{}", if tuple { ")" } else { " }" })
+        }
+        match self {
+            DynValue::Unit => write!(f, "()"),
+            DynValue::Bool(value) => write!(f, "{}", value),
+            DynValue::Uint { value, .. } => write!(f, "{}", value),
+            DynValue::Int { value, .. } => write!(f, "{}", value),
+            DynValue::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            DynValue::Struct(values) => fields(f, values),
+            DynValue::Variant {
+                name,
+                fields: values,
+            } => {
+                write!(f, "{}", name)?;
+                if values.is_empty() {
+                    return Ok(());
+                }
+                if !is_tuple(values) {
+                    write!(f, " ")?;
+                }
+                fields(f, values)
+            }
+            DynValue::Opaque { bytes, .. } => {
+                write!(f, "0x")?;
+                for byte in bytes.iter().rev() {
+                    write!(f, "{:02x}", byte)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The error of `get_path` and `set_path`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathError {
+    /// The path can't be parsed, at the byte `position`.
+    Syntax { position: usize },
+    /// `.field` on a type without that field.
+    NoField {
+        type_name: &'static str,
+        field: String,
+    },
+    /// `?Variant` on a type without that variant.
+    NoVariant {
+        type_name: &'static str,
+        variant: String,
+    },
+    /// `[index]` past the end of an array.
+    OutOfBounds { index: usize, len: usize },
+    /// `[index]` on a type that isn't an array.
+    NotArray { type_name: &'static str },
+    /// `?Variant` while the bits hold another variant.
+    VariantMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// The bits hold a tag without a variant.
+    UnknownTag { type_name: &'static str, tag: u32 },
+    /// `set_path` with a value of another kind than the type at the path.
+    TypeMismatch { type_name: &'static str },
+    /// `set_path` with a number that doesn't fit in the bits at the path.
+    Overflow { bits: usize },
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathError::Syntax { position } => write!(f, "invalid path at byte {}", position),
+            PathError::NoField { type_name, field } => {
+                write!(f, "`{}` has no field `{}`", type_name, field)
+            }
+            PathError::NoVariant { type_name, variant } => {
+                write!(f, "`{}` has no variant `{}`", type_name, variant)
+            }
+            PathError::OutOfBounds { index, len } => write!(
+                f,
+                "index {} is out of bounds for an array of length {}",
+                index, len
+            ),
+            PathError::NotArray { type_name } => write!(f, "`{}` can't be indexed", type_name),
+            PathError::VariantMismatch { expected, found } => {
+                write!(f, "expected variant `{}`, found `{}`", expected, found)
+            }
+            PathError::UnknownTag { type_name, tag } => {
+                write!(f, "`{}` has no variant with tag {}", type_name, tag)
+            }
+            PathError::TypeMismatch { type_name } => {
+                write!(f, "the value doesn't match the shape of `{}`", type_name)
+            }
+            PathError::Overflow { bits } => write!(f, "the value doesn't fit in {} bits", bits),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+/// A step of a path.
+enum Segment<'p> {
+    Field(&'p str),
+    Index(usize),
+    Variant(&'p str),
+}
+
+/// Splits a path like `header.flags[3]` or `kind?Data.len` into its segments.
+fn parse_path(path: &str) -> Result<Vec<Segment<'_>>, PathError> {
+    let ident_end = |start: usize| {
+        path[start..]
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .map_or(path.len(), |end| start + end)
+    };
+    let mut segments = Vec::new();
+    let mut position = 0;
+    while position < path.len() {
+        let (segment, end) =
+            match path.as_bytes()[position] {
+                b'.' | b'?' => {
+                    let end = ident_end(position + 1);
+                    let name = &path[position + 1..end];
+                    if name.is_empty() {
+                        return Err(PathError::Syntax { position: end });
+                    }
+                    if path.as_bytes()[position] == b'.' {
+                        (Segment::Field(name), end)
+                    } else {
+                        (Segment::Variant(name), end)
+                    }
+                }
+                b'[' => {
+                    let close = path[position..]
+                        .find(']')
+                        .map(|close| position + close)
+                        .ok_or(PathError::Syntax { position })?;
+                    let index = path[position + 1..close].trim().parse().map_err(|_| {
+                        PathError::Syntax {
+                            position: position + 1,
+                        }
+                    })?;
+                    (Segment::Index(index), close + 1)
+                }
+                // The first field doesn't need a dot.
+                _ if position == 0 => {
+                    let end = ident_end(0);
+                    if end == 0 {
+                        return Err(PathError::Syntax { position });
+                    }
+                    (Segment::Field(&path[..end]), end)
+                }
+                _ => return Err(PathError::Syntax { position }),
+            };
+        segments.push(segment);
+        position = end;
+    }
+    Ok(segments)
+}
+
+/// What a path leads to. Offsets are in bits from the start of the bytes.
+enum Place<'l> {
+    Value {
+        layout: &'l Layout,
+        offset: usize,
+    },
+    /// The fields of a variant after `?Variant`, offset is where the enum starts.
+    Variant {
+        layout: &'l Layout,
+        variant: &'l VariantLayout,
+        offset: usize,
+    },
+}
+
+/// Follows `path` from a value with `layout` at `offset`.
+fn resolve<'l>(
+    bytes: &[u8],
+    layout: &'l Layout,
+    offset: usize,
+    path: &str,
+) -> Result<Place<'l>, PathError> {
+    let mut place = Place::Value { layout, offset };
+    for segment in parse_path(path)? {
+        place = match (segment, place) {
+            (Segment::Field(name), Place::Value { layout, offset }) => {
+                let field = layout.field(name).ok_or_else(|| PathError::NoField {
+                    type_name: layout.type_name,
+                    field: name.to_owned(),
+                })?;
+                Place::Value {
+                    layout: &field.layout,
+                    offset: offset + field.offset,
+                }
+            }
+            (
+                Segment::Field(name),
+                Place::Variant {
+                    layout,
+                    variant,
+                    offset,
+                },
+            ) => {
+                let field = variant
+                    .fields
+                    .iter()
+                    .find(|field| field.name == name)
+                    .ok_or_else(|| PathError::NoField {
+                        type_name: layout.type_name,
+                        field: format!("{}.{}", variant.name, name),
+                    })?;
+                Place::Value {
+                    layout: &field.layout,
+                    offset: offset + field.offset,
+                }
+            }
+            (Segment::Index(index), Place::Value { layout, offset }) => match &layout.shape {
+                Shape::Array { len, element } if index < *len => Place::Value {
+                    layout: element,
+                    offset: offset + index * element.bits,
+                },
+                Shape::Array { len, .. } => {
+                    return Err(PathError::OutOfBounds { index, len: *len })
+                }
+                _ => {
+                    return Err(PathError::NotArray {
+                        type_name: layout.type_name,
+                    })
+                }
+            },
+            (Segment::Index(_), Place::Variant { layout, .. }) => {
+                return Err(PathError::NotArray {
+                    type_name: layout.type_name,
+                })
+            }
+            (Segment::Variant(name), Place::Value { layout, offset }) => {
+                let variant = layout.variant(name).ok_or_else(|| PathError::NoVariant {
+                    type_name: layout.type_name,
+                    variant: name.to_owned(),
+                })?;
+                let found = read_variant(bytes, layout, offset)?;
+                if found.name != variant.name {
+                    return Err(PathError::VariantMismatch {
+                        expected: variant.name,
+                        found: found.name,
+                    });
+                }
+                Place::Variant {
+                    layout,
+                    variant,
+                    offset,
+                }
+            }
+            (Segment::Variant(name), Place::Variant { layout, .. }) => {
+                return Err(PathError::NoVariant {
+                    type_name: layout.type_name,
+                    variant: name.to_owned(),
+                })
+            }
+        };
+    }
+    Ok(place)
+}
+
+/// Reads `bits` bits from `offset`, the lowest bit first.
+pub fn read_bits(bytes: &[u8], offset: usize, bits: usize) -> u128 {
+    (0..bits).fold(0, |value, i| {
+        let bit = offset + i;
+        value | (((bytes[bit / 8] >> (bit % 8)) & 1) as u128) << i
+    })
+}
+
+/// Writes the lowest `bits` bits of `value` at `offset`.
+fn write_bits(bytes: &mut [u8], offset: usize, bits: usize, value: u128) {
+    for i in 0..bits {
+        let bit = offset + i;
+        if (value >> i) & 1 == 1 {
+            bytes[bit / 8] |= 1 << (bit % 8);
+        } else {
+            bytes[bit / 8] &= !(1 << (bit % 8));
+        }
+    }
+}
+
+/// Reads `bits` bits from `offset` into bytes of their own, the lowest bit first.
+pub(crate) fn read_opaque(bytes: &[u8], offset: usize, bits: usize) -> Vec<u8> {
+    (0..bits)
+        .step_by(8)
+        .map(|i| read_bits(bytes, offset + i, (bits - i).min(8)) as u8)
+        .collect()
+}
+
+/// The `bits` bits from `offset` as `0`s and `1`s, the highest bit first.
+fn bit_string(bytes: &[u8], offset: usize, bits: usize) -> String {
+    (0..bits)
+        .rev()
+        .map(|i| {
+            let bit = offset + i;
+            if (bytes[bit / 8] >> (bit % 8)) & 1 == 1 {
+                '1'
+            } else {
+                '0'
+            }
+        })
+        .collect()
+}
+
+fn mask(bits: usize) -> u128 {
+    if bits >= 128 {
+        u128::MAX
+    } else {
+        !(u128::MAX << bits)
+    }
+}
+
+/// Sign extends the lowest `bits` bits of `raw`.
+fn sign_extend(raw: u128, bits: usize) -> i128 {
+    if bits == 0 || bits >= 128 {
+        raw as i128
+    } else {
+        ((raw << (128 - bits)) as i128) >> (128 - bits)
+    }
+}
+
+fn fits_signed(value: i128, bits: usize) -> bool {
+    bits >= 128 || sign_extend(value as u128 & mask(bits), bits) == value
+}
+
+/// The variant of the enum with `layout` at `offset`.
+fn read_variant<'l>(
+    bytes: &[u8],
+    layout: &'l Layout,
+    offset: usize,
+) -> Result<&'l VariantLayout, PathError> {
+    match layout.shape {
+        Shape::Enum {
+            tag_offset,
+            tag_bits,
+            ..
+        } => {
+            let tag = read_bits(bytes, offset + tag_offset, tag_bits) as u32;
+            layout.variant_of_tag(tag).ok_or(PathError::UnknownTag {
+                type_name: layout.type_name,
+                tag,
+            })
+        }
+        _ => unreachable!("only enums have variants"),
+    }
+}
+
+fn read_fields(
+    bytes: &[u8],
+    fields: &[FieldLayout],
+    offset: usize,
+) -> Result<Vec<(String, DynValue)>, PathError> {
+    fields
+        .iter()
+        .map(|field| {
+            Ok((
+                field.name.to_owned(),
+                read(bytes, &field.layout, offset + field.offset)?,
+            ))
+        })
+        .collect()
+}
+
+/// Reads a number, `bool`, `()` or opaque value with `shape` at `offset`, `None` for every other
+/// shape.
+pub(crate) fn read_leaf(
+    bytes: &[u8],
+    shape: &Shape,
+    offset: usize,
+    bits: usize,
+) -> Option<DynValue> {
+    let raw = || read_bits(bytes, offset, bits);
+    Some(match shape {
+        Shape::Unit => DynValue::Unit,
+        Shape::Bool => DynValue::Bool(raw() != 0),
+        Shape::Uint => DynValue::Uint { bits, value: raw() },
+        Shape::Int => DynValue::Int {
+            bits,
+            value: sign_extend(raw(), bits),
+        },
+        Shape::Encoded(Encoding::Gray) => {
+            let mut value = raw();
+            let mut shift = 1;
+            while shift < 128 {
+                value ^= value >> shift;
+                shift <<= 1;
+            }
+            DynValue::Uint { bits, value }
+        }
+        Shape::Encoded(Encoding::ZigZag) => {
+            let raw = raw();
+            DynValue::Int {
+                bits,
+                value: ((raw >> 1) as i128) ^ -((raw & 1) as i128),
+            }
+        }
+        Shape::Encoded(Encoding::SignMag) => {
+            let raw = raw();
+            let magnitude = (raw & mask(bits - 1)) as i128;
+            let negative = (raw >> (bits - 1)) & 1 == 1;
+            DynValue::Int {
+                bits,
+                value: if negative { -magnitude } else { magnitude },
+            }
+        }
+        Shape::Opaque => DynValue::Opaque {
+            bits,
+            bytes: read_opaque(bytes, offset, bits),
+        },
+        _ => return None,
+    })
+}
+
+/// Reads the value with `layout` at `offset`.
+fn read(bytes: &[u8], layout: &Layout, offset: usize) -> Result<DynValue, PathError> {
+    if let Some(value) = read_leaf(bytes, &layout.shape, offset, layout.bits) {
+        return Ok(value);
+    }
+    Ok(match &layout.shape {
+        Shape::Struct(fields) | Shape::Union(fields) => {
+            DynValue::Struct(read_fields(bytes, fields, offset)?)
+        }
+        Shape::Enum { .. } => {
+            let variant = read_variant(bytes, layout, offset)?;
+            DynValue::Variant {
+                name: variant.name.to_owned(),
+                fields: read_fields(bytes, &variant.fields, offset)?,
+            }
+        }
+        Shape::Array { len, element } => DynValue::Array(
+            (0..*len)
+                .map(|i| read(bytes, element, offset + i * element.bits))
+                .collect::<Result<_, _>>()?,
+        ),
+        _ => unreachable!("leaves are read by `read_leaf`"),
+    })
+}
+
+/// Writes the named `values` to their fields in `fields`, leaving the other fields as they are.
+fn write_fields(
+    bytes: &mut [u8],
+    layout: &Layout,
+    fields: &[FieldLayout],
+    offset: usize,
+    values: &[(String, DynValue)],
+) -> Result<(), PathError> {
+    for (name, value) in values {
+        let field = fields
+            .iter()
+            .find(|field| field.name == name)
+            .ok_or_else(|| PathError::NoField {
+                type_name: layout.type_name,
+                field: name.clone(),
+            })?;
+        write(bytes, &field.layout, offset + field.offset, value)?;
+    }
+    Ok(())
+}
+
+/// Writes the tag of `variant` and its `values`, the fields without a value are zeroed.
+fn write_variant(
+    bytes: &mut [u8],
+    layout: &Layout,
+    variant: &VariantLayout,
+    offset: usize,
+    values: &[(String, DynValue)],
+) -> Result<(), PathError> {
+    match (&layout.shape, variant.tag) {
+        (
+            Shape::Enum {
+                tag_offset,
+                tag_bits,
+                ..
+            },
+            Some(tag),
+        ) => write_bits(bytes, offset + tag_offset, *tag_bits, tag as u128),
+        // The other variant is written through its tag, which no other variant may have.
+        _ => {
+            let tag = values
+                .iter()
+                .find(|(name, _)| name == "0")
+                .and_then(|(_, value)| value.as_uint())
+                .ok_or(PathError::TypeMismatch {
+                    type_name: layout.type_name,
+                })?;
+            match u32::try_from(tag)
+                .ok()
+                .and_then(|tag| layout.variant_of_tag(tag))
+            {
+                Some(found) if found.tag.is_some() => {
+                    return Err(PathError::VariantMismatch {
+                        expected: variant.name,
+                        found: found.name,
+                    })
+                }
+                _ => {}
+            }
+        }
+    }
+    // The bits of the fields that aren't given may be left from another variant.
+    for field in &variant.fields {
+        if !values.iter().any(|(name, _)| name == field.name) {
+            write_bits(bytes, offset + field.offset, field.bits, 0);
+        }
+    }
+    write_fields(bytes, layout, &variant.fields, offset, values)
+}
+
+/// Writes `value` as the type with `layout` at `offset`. When the value doesn't fit, what came
+/// before it, like the tag of a variant or the fields of a struct, has already been written.
+fn write(
+    bytes: &mut [u8],
+    layout: &Layout,
+    offset: usize,
+    value: &DynValue,
+) -> Result<(), PathError> {
+    let bits = layout.bits;
+    let mismatch = || PathError::TypeMismatch {
+        type_name: layout.type_name,
+    };
+    let unsigned = |value: u128| {
+        if value & !mask(bits) == 0 {
+            Ok(value)
+        } else {
+            Err(PathError::Overflow { bits })
+        }
+    };
+    let signed = |value: i128| {
+        if fits_signed(value, bits) {
+            Ok(value)
+        } else {
+            Err(PathError::Overflow { bits })
+        }
+    };
+    let raw = match (&layout.shape, value) {
+        (Shape::Unit, DynValue::Unit) => return Ok(()),
+        (Shape::Bool, DynValue::Bool(value)) => *value as u128,
+        (Shape::Uint, DynValue::Uint { value, .. }) => unsigned(*value)?,
+        (Shape::Int, DynValue::Int { value, .. }) => signed(*value)? as u128 & mask(bits),
+        (Shape::Encoded(Encoding::Gray), DynValue::Uint { value, .. }) => {
+            let value = unsigned(*value)?;
+            value ^ (value >> 1)
+        }
+        (Shape::Encoded(Encoding::ZigZag), DynValue::Int { value, .. }) => {
+            let value = signed(*value)?;
+            ((value << 1) ^ (value >> 127)) as u128 & mask(bits)
+        }
+        (Shape::Encoded(Encoding::SignMag), DynValue::Int { value, .. }) => {
+            if value.unsigned_abs() > mask(bits - 1) {
+                return Err(PathError::Overflow { bits });
+            }
+            ((*value < 0) as u128) << (bits - 1) | value.unsigned_abs()
+        }
+        (
+            Shape::Opaque,
+            DynValue::Opaque {
+                bits: len,
+                bytes: values,
+            },
+        ) if *len == bits && values.len() == (bits + 7) / 8 => {
+            if bits % 8 != 0 && values[values.len() - 1] >> (bits % 8) != 0 {
+                return Err(PathError::Overflow { bits });
+            }
+            for (i, byte) in values.iter().enumerate() {
+                write_bits(bytes, offset + i * 8, (bits - i * 8).min(8), *byte as u128);
+            }
+            return Ok(());
+        }
+        (Shape::Struct(fields), DynValue::Struct(values)) => {
+            return write_fields(bytes, layout, fields, offset, values)
+        }
+        // Writing more than one view of a union would overwrite the others.
+        (Shape::Union(fields), DynValue::Struct(values)) if values.len() == 1 => {
+            return write_fields(bytes, layout, fields, offset, values)
+        }
+        (Shape::Enum { .. }, DynValue::Variant { name, fields }) => {
+            let variant = layout.variant(name).ok_or_else(|| PathError::NoVariant {
+                type_name: layout.type_name,
+                variant: name.clone(),
+            })?;
+            return write_variant(bytes, layout, variant, offset, fields);
+        }
+        (Shape::Array { len, element }, DynValue::Array(values)) if values.len() == *len => {
+            for (i, value) in values.iter().enumerate() {
+                write(bytes, element, offset + i * element.bits, value)?;
+            }
+            return Ok(());
+        }
+        _ => return Err(mismatch()),
+    };
+    write_bits(bytes, offset, bits, raw);
+    Ok(())
+}
+
+/// The value at `path` from a value with `layout` at `offset` in `bytes`.
+pub(crate) fn get_path(
+    bytes: &[u8],
+    layout: &Layout,
+    offset: usize,
+    path: &str,
+) -> Result<DynValue, PathError> {
+    match resolve(bytes, layout, offset, path)? {
+        Place::Value { layout, offset } => read(bytes, layout, offset),
+        Place::Variant {
+            variant, offset, ..
+        } => Ok(DynValue::Struct(read_fields(
+            bytes,
+            &variant.fields,
+            offset,
+        )?)),
+    }
+}
+
+/// Writes `value` at `path` from a value with `layout` at `offset` in `bytes`. The bytes are left
+/// as they were when it fails.
+pub(crate) fn set_path(
+    bytes: &mut [u8],
+    layout: &Layout,
+    offset: usize,
+    path: &str,
+    value: &DynValue,
+) -> Result<(), PathError> {
+    // `write` can fail after writing a tag or some of the fields, so it writes to a copy.
+    let mut scratch = bytes.to_vec();
+    match resolve(bytes, layout, offset, path)? {
+        Place::Value { layout, offset } => write(&mut scratch, layout, offset, value)?,
+        Place::Variant {
+            layout,
+            variant,
+            offset,
+        } => match value {
+            DynValue::Struct(values) => {
+                write_fields(&mut scratch, layout, &variant.fields, offset, values)?
+            }
+            _ => {
+                return Err(PathError::TypeMismatch {
+                    type_name: layout.type_name,
+                })
+            }
+        },
+    }
+    bytes.copy_from_slice(&scratch);
+    Ok(())
+}
+
+/// A line of `write_diagram`, a number, `bool`, enum tag or opaque value.
+struct Row {
+    range: Range<usize>,
+    bits: String,
+    path: String,
+    value: String,
+}
+
+/// Collects the rows of the value with `layout` at `offset`, the ranges start at `start`.
+fn diagram_rows(
+    bytes: &[u8],
+    layout: &Layout,
+    offset: usize,
+    start: usize,
+    path: &str,
+    rows: &mut Vec<Row>,
+) {
+    let row = |offset: usize, bits: usize, path: String, value: String| Row {
+        range: offset - start..offset - start + bits,
+        bits: bit_string(bytes, offset, bits),
+        path,
+        value,
+    };
+    let field_path = |name: &str| {
+        if path.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{}.{}", path, name)
+        }
+    };
+    match &layout.shape {
+        Shape::Unit => {}
+        Shape::Struct(fields) | Shape::Union(fields) => {
+            for field in fields {
+                diagram_rows(
+                    bytes,
+                    &field.layout,
+                    offset + field.offset,
+                    start,
+                    &field_path(field.name),
+                    rows,
+                );
+            }
+        }
+        Shape::Array { len, element } => {
+            for i in 0..*len {
+                let path = format!("{}[{}]", path, i);
+                diagram_rows(
+                    bytes,
+                    element,
+                    offset + i * element.bits,
+                    start,
+                    &path,
+                    rows,
+                );
+            }
+        }
+        Shape::Enum {
+            tag_offset,
+            tag_bits,
+            ..
+        } => {
+            let variant = read_variant(bytes, layout, offset);
+            if *tag_bits > 0 {
+                let tag = read_bits(bytes, offset + tag_offset, *tag_bits);
+                let value = match variant {
+                    Ok(variant) => format!("{} (tag {})", variant.name, tag),
+                    Err(_) => format!("unknown tag {}", tag),
+                };
+                rows.push(row(offset + tag_offset, *tag_bits, path.to_owned(), value));
+            }
+            if let Ok(variant) = variant {
+                for field in &variant.fields {
+                    let path = format!("{}?{}.{}", path, variant.name, field.name);
+                    diagram_rows(
+                        bytes,
+                        &field.layout,
+                        offset + field.offset,
+                        start,
+                        &path,
+                        rows,
+                    );
+                }
+            }
+        }
+        shape => {
+            let value = read_leaf(bytes, shape, offset, layout.bits).unwrap();
+            rows.push(row(offset, layout.bits, path.to_owned(), value.to_string()));
+        }
+    }
+}
+
+/// Writes a line for every number, `bool` and enum tag of the value with `layout` at `offset`,
+/// with its bit range, its bits from the highest one and its value.
+pub(crate) fn write_diagram(
+    f: &mut fmt::Formatter,
+    bytes: &[u8],
+    layout: &Layout,
+    offset: usize,
+) -> fmt::Result {
+    let mut rows = Vec::new();
+    diagram_rows(bytes, layout, offset, offset, "", &mut rows);
+    let ranges: Vec<_> = rows
+        .iter()
+        .map(|row| format!("{}..{}", row.range.start, row.range.end))
+        .collect();
+    let range_width = ranges.iter().map(String::len).max().unwrap_or(0);
+    let bits_width = rows.iter().map(|row| row.range.len()).max().unwrap_or(0);
+    let path_width = rows.iter().map(|row| row.path.len()).max().unwrap_or(0);
+    writeln!(f, "Bit<{}> ({} bits) {{", layout.type_name, layout.bits)?;
+    for (row, range) in rows.iter().zip(&ranges) {
+        writeln!(
+            f,
+            "    {:>range_width$}  {:<bits_width$}  {:<path_width$}  {}",
+            range,
+            row.bits,
+            row.path,
+            row.value,
+            range_width = range_width,
+            bits_width = bits_width,
+            path_width = path_width,
+        )?;
+    }
+    write!(f, "}}")
+}
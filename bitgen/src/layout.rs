@@ -0,0 +1,191 @@
+use std::any;
+
+use crate::{dynamic::read_opaque, BitType, BitVisitor};
+
+/// Describes at runtime where every field of a `BitType` is stored, derived together with
+/// `BitType`. Meant for tools that inspect packed values without knowing their type, which can
+/// require it as a bound. A type with a hand-written `BitType` impl can opt in with an empty
+/// `impl BitLayout for T {}`, which describes it as `Shape::Opaque`, its raw bits.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` doesn't implement `BitLayout`",
+    note = "`BitLayout` is derived with `BitType`, for a hand-written `BitType` impl add `impl BitLayout for {Self} {{}}` to inspect it as its raw bits"
+)]
+pub trait BitLayout: BitType {
+    fn layout() -> Layout {
+        Layout::new::<Self>(Shape::Opaque)
+    }
+
+    /// Walks the value packed `offset` bits into `bytes` with `visitor`, without extracting it.
+    fn visit<V: BitVisitor + ?Sized>(bytes: &[u8], offset: usize, visitor: &mut V) {
+        visitor.visit_opaque(
+            any::type_name::<Self>(),
+            Self::BITS,
+            &read_opaque(bytes, offset, Self::BITS),
+        )
+    }
+}
+
+/// The layout of a type, see `BitLayout`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layout {
+    /// The name of the type, as given by `std::any::type_name`.
+    pub type_name: &'static str,
+    pub bits: usize,
+    pub shape: Shape,
+}
+
+/// What a type is made of. Offsets are in bits from the start of the type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Shape {
+    /// `()`, which takes no bits.
+    Unit,
+    Bool,
+    /// An unsigned integer, `u8` to `u64` or `U<N>`.
+    Uint,
+    /// A two's complement integer, `i8` to `i64` or `I<N>`.
+    Int,
+    /// A number stored in an encoding other than plain binary.
+    Encoded(Encoding),
+    /// A struct or tuple, tuple fields are named `0`, `1`, ...
+    Struct(Vec<FieldLayout>),
+    /// A union, every field starts at 0.
+    Union(Vec<FieldLayout>),
+    /// A tagged enum, `Option` is one with `None` and `Some`.
+    Enum {
+        tag_offset: usize,
+        tag_bits: usize,
+        variants: Vec<VariantLayout>,
+    },
+    /// An array of `len` elements one after another.
+    Array {
+        len: usize,
+        element: Box<Layout>,
+    },
+    /// A type that doesn't describe its fields, like one with a hand-written `BitType` impl. It's
+    /// read and written as its raw bits.
+    Opaque,
+}
+
+/// The encodings of `Gray`, `ZigZag` and `SignMag`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    Gray,
+    ZigZag,
+    SignMag,
+}
+
+/// A field of a struct, union or enum variant.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldLayout {
+    pub name: &'static str,
+    pub offset: usize,
+    pub bits: usize,
+    /// The type of the field as declared, which can differ from the type in `layout` for fields
+    /// with `#[bitgen(bits = N)]` or `#[bitgen(with = "...")]`.
+    pub type_name: &'static str,
+    pub layout: Layout,
+}
+
+/// A variant of an enum.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariantLayout {
+    pub name: &'static str,
+    /// The tag of the variant, `None` for the `#[bitgen(other)]` variant which has every tag
+    /// that isn't taken.
+    pub tag: Option<u32>,
+    /// The fields, their offsets are from the start of the enum.
+    pub fields: Vec<FieldLayout>,
+}
+
+impl Layout {
+    /// The layout of `T` with `shape`.
+    pub fn new<T: BitType>(shape: Shape) -> Self {
+        Layout {
+            type_name: any::type_name::<T>(),
+            bits: T::BITS,
+            shape,
+        }
+    }
+
+    /// The fields of a struct or union, empty for every other shape.
+    pub fn fields(&self) -> &[FieldLayout] {
+        match &self.shape {
+            Shape::Struct(fields) | Shape::Union(fields) => fields,
+            _ => &[],
+        }
+    }
+
+    /// The field of a struct or union called `name`.
+    pub fn field(&self, name: &str) -> Option<&FieldLayout> {
+        self.fields().iter().find(|field| field.name == name)
+    }
+
+    /// The variants of an enum, empty for every other shape.
+    pub fn variants(&self) -> &[VariantLayout] {
+        match &self.shape {
+            Shape::Enum { variants, .. } => variants,
+            _ => &[],
+        }
+    }
+
+    /// The variant of an enum called `name`.
+    pub fn variant(&self, name: &str) -> Option<&VariantLayout> {
+        self.variants().iter().find(|variant| variant.name == name)
+    }
+
+    /// The variant of an enum with `tag`, which is the `#[bitgen(other)]` variant if no other
+    /// variant has it.
+    pub fn variant_of_tag(&self, tag: u32) -> Option<&VariantLayout> {
+        let variants = self.variants();
+        variants
+            .iter()
+            .find(|variant| variant.tag == Some(tag))
+            .or_else(|| variants.iter().find(|variant| variant.tag.is_none()))
+    }
+}
+
+impl FieldLayout {
+    /// A field of type `T` at `offset`.
+    pub fn of<T: BitLayout>(name: &'static str, offset: usize) -> Self {
+        FieldLayout {
+            name,
+            offset,
+            bits: T::BITS,
+            type_name: any::type_name::<T>(),
+            layout: T::layout(),
+        }
+    }
+
+    /// The bits of the field, from the start of the type it's in.
+    pub fn range(&self) -> std::ops::Range<usize> {
+        self.offset..self.offset + self.bits
+    }
+}
+
+/// The `layout` and `visit` of a number, `bool` or `()` with `shape`, for its `BitLayout` impl, or
+/// the whole impl for every type listed after it.
+macro_rules! leaf_layout {
+    ($shape:expr; $($ty:ty),+) => {
+        $(
+            impl crate::BitLayout for $ty {
+                crate::layout::leaf_layout!($shape);
+            }
+        )+
+    };
+    ($shape:expr) => {
+        fn layout() -> crate::Layout {
+            crate::Layout::new::<Self>($shape)
+        }
+
+        fn visit<V: crate::BitVisitor + ?Sized>(bytes: &[u8], offset: usize, visitor: &mut V) {
+            crate::visitor::visit_leaf(
+                bytes,
+                &$shape,
+                offset,
+                <Self as crate::BitType>::BITS,
+                visitor,
+            )
+        }
+    };
+}
+pub(crate) use leaf_layout;
@@ -5,23 +5,36 @@
 #![feature(const_for)]
 #![feature(const_fn_trait_bound)]
 #![feature(associated_type_defaults)]
+#![feature(step_trait)]
 
 mod bit_num;
 
 mod bit_type;
 mod bit_wrapper;
 mod containers;
+mod dynamic;
+mod layout;
 mod magic;
+mod visitor;
 
-pub use crate::bit_num::{ibits, ubits, I, U};
+#[cfg(feature = "proptest")]
+mod arbitrary;
+#[cfg(feature = "rand")]
+mod random;
+
+pub use crate::bit_num::{ibits, ubits, Gray, ParseBitsError, SignMag, ZigZag, I, U};
 
 #[cfg(feature = "derive")]
 pub use bitgen_derive::BitType;
 
-pub use crate::bit_type::BitType;
+pub use crate::bit_type::{BitType, BitUnion, Remote};
 pub use crate::bit_wrapper::{
-    accessors::DynAccess, accessors::MaybeAccess, accessors::TupleAccess, Accessor,
+    accessors::DynAccess, accessors::MaybeAccess, accessors::TupleAccess, Accessor, BitIter,
+    BitZip, IterMut, VariantMismatch,
 };
+pub use crate::dynamic::{DynValue, PathError};
+pub use crate::layout::{BitLayout, Encoding, FieldLayout, Layout, Shape, VariantLayout};
+pub use crate::visitor::BitVisitor;
 pub use containers::*;
 
 /// Constant hash function for string
@@ -29,37 +42,74 @@ pub const fn hash_ident(ident: &str) -> usize {
     const_fnv1a_hash::fnv1a_hash_str_64(ident) as usize
 }
 pub mod internal {
-    pub use crate::bit_wrapper::get_byte_range;
+    pub use crate::bit_num::{narrow_signed, narrow_unsigned};
+    pub use crate::bit_wrapper::{
+        access::Access, clear_bits, get_byte_range, ChildAccess, DynIndex,
+    };
+    pub use crate::dynamic::read_bits;
+    pub use crate::magic::InferEq;
+    pub use num_traits::AsPrimitive;
+    pub use wyz::{Const, Mut, Mutability};
+
+    /// Only compiles for unions that promise to hold their first field, see `BitUnion`.
+    pub fn assert_bit_union<T: crate::BitUnion>() {}
+
+    /// The larger of `a` and `b`, usable in constants.
+    pub const fn max(a: usize, b: usize) -> usize {
+        if a > b {
+            a
+        } else {
+            b
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    pub use proptest;
 }
 
 /// Only used internally for the bit! macro.
+///
+/// After a checked index the rest of the path is in `@opt` mode, which wraps the result in
+/// `Some`, so every checked index of a path shares one `Option`.
 #[macro_export]
 macro_rules! bit_tail {
-    ($expr:expr; [$elem:literal] $($tail:tt)*) => {
-        bitgen::bit_tail!(bitgen::Accessor::get::<$elem>($expr); $($tail)*)
+    ($(@$mode:ident)? $expr:expr; [$elem:literal] $($tail:tt)*) => {
+        bitgen::bit_tail!($(@$mode)? bitgen::Accessor::get::<$elem>($expr); $($tail)*)
     };
 
-    ($expr:expr; [$elem:expr] $($tail:tt)*) => {
-        bitgen::bit_tail!(bitgen::Accessor::get_dyn($expr, $elem); $($tail)*)
+    ($(@$mode:ident)? $expr:expr; [?$elem:expr] $($tail:tt)*) => {
+        bitgen::Accessor::try_get_dyn($expr, $elem).and_then(|child| bitgen::bit_tail!(@opt child; $($tail)*))
     };
 
-    ($expr:expr; .$elem:ident $($tail:tt)*) => {
-        bitgen::bit_tail!(bitgen::Accessor::get::<{hash_ident(stringify!($elem))}>($expr); $($tail)*)
+    ($(@$mode:ident)? $expr:expr; [$elem:expr] $($tail:tt)*) => {
+        bitgen::bit_tail!($(@$mode)? bitgen::internal::DynIndex::index($elem, $expr); $($tail)*)
     };
-    ($expr:expr; .$elem:literal $($tail:tt)*) => {
-        bitgen::bit_tail!(bitgen::Accessor::get::<$elem>($expr); $($tail)*)
+
+    ($(@$mode:ident)? $expr:expr; .$elem:ident $($tail:tt)*) => {
+        bitgen::bit_tail!($(@$mode)? bitgen::Accessor::get::<{hash_ident(stringify!($elem))}>($expr); $($tail)*)
+    };
+    ($(@$mode:ident)? $expr:expr; .$elem:literal $($tail:tt)*) => {
+        bitgen::bit_tail!($(@$mode)? bitgen::Accessor::get::<$elem>($expr); $($tail)*)
+    };
+    ($(@$mode:ident)? $expr:expr; .($elem:expr) $($tail:tt)*) => {
+        bitgen::bit_tail!($(@$mode)? bitgen::Accessor::get::<$elem>($expr); $($tail)*)
     };
-    ($expr:expr; .($elem:expr) $($tail:tt)*) => {
-        bitgen::bit_tail!(bitgen::Accessor::get::<$elem>($expr); $($tail)*)
+
+    ($(@$mode:ident)? $expr:expr; ?$elem:ident $($tail:tt)*) => {
+        bitgen::bit_tail!($(@$mode)? bitgen::Accessor::get_maybe::<{hash_ident(stringify!($elem))}>($expr); $($tail)*)
+    };
+    ($(@$mode:ident)? $expr:expr; ? $($tail:tt)*) => {
+        bitgen::bit_tail!($(@$mode)? bitgen::Accessor::get_maybe::<{hash_ident("Some")}>($expr); $($tail)*)
     };
 
-    ($expr:expr; ?$elem:ident $($tail:tt)*) => {
-        bitgen::bit_tail!(bitgen::Accessor::get_maybe::<{hash_ident(stringify!($elem))}>($expr); $($tail)*)
+    ($(@$mode:ident)? $expr:expr; = ?$elem:ident($payload:expr)) => {
+        bitgen::bit_tail!($(@$mode)? bitgen::Accessor::set_variant::<{hash_ident(stringify!($elem))}>(&$expr, $payload);)
     };
-    ($expr:expr; ? $($tail:tt)*) => {
-        bitgen::bit_tail!(bitgen::Accessor::get_maybe::<{hash_ident("Some")}>($expr); $($tail)*)
+    ($(@$mode:ident)? $expr:expr; = ?$elem:ident) => {
+        bitgen::bit_tail!($(@$mode)? bitgen::Accessor::set_variant::<{hash_ident(stringify!($elem))}>(&$expr, ());)
     };
 
+    (@opt $expr:expr;) => { Some($expr) };
     ($expr:expr;) => { $expr }
 }
 
@@ -93,9 +143,15 @@ macro_rules! bit_tail {
 /// When accessing fields in the bit macro you can use the following:
 /// - tuple accessor i.e `.0`, `.1`, `.2`, ...
 /// - index accessor i.e `[0]`, `[1]`, `[2]`, ... This also accepts expressions so can be used dynamicly contrary to the tuple accessor
+/// - range accessor i.e `[2..5]`, `[..n]`, `[i..]`, ... Gives an accessor over the elements in the range, index it with an expression.
+/// - checked index accessor i.e `[?i]`, returns `None` instead of panicking when `i` is out of bounds, otherwise the rest of the path in `Some`. A path with several checked indices still returns one `Option`.
 /// - struct accessor i.e `.field`, ...
 /// - maybe accessor, used for getting an enum variant. i.e `?Some`, `?None`. This will return an Option<T> when later accessing a field.
 ///
+/// # Setting variants
+/// `bit!(mut x.field = ?Variant(payload))` switches an enum to `Variant` in place, writing its tag and payload.
+/// The payload of a unit variant can be left out, i.e `bit!(mut x.field = ?None)`. It calls `Accessor::set_variant`.
+///
 /// # Casting
 /// This essentially a transmute and is therefore unsafe.
 /// Used by having a type enclosed in parenthesis at the start of the bit! macro.
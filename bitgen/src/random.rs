@@ -0,0 +1,31 @@
+use rand::{
+    distributions::{Distribution, Standard},
+    Rng,
+};
+
+use crate::{
+    bit_num::{Type, Underlying},
+    I, U,
+};
+
+impl<const N: usize> Distribution<U<N>> for Standard
+where
+    Underlying<N>: Type,
+    Standard: Distribution<<Underlying<N> as Type>::U>,
+{
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> U<N> {
+        U::new(rng.gen::<<Underlying<N> as Type>::U>() & U::<N>::MAX.extract_underlying())
+    }
+}
+
+impl<const N: usize> Distribution<I<N>> for Standard
+where
+    Underlying<N>: Type,
+    Standard: Distribution<<Underlying<N> as Type>::I>,
+{
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> I<N> {
+        // Sign extend the lowest N bits so the value is in range.
+        let shift = <Underlying<N> as Type>::BITS - N;
+        I::new((rng.gen::<<Underlying<N> as Type>::I>() << shift) >> shift)
+    }
+}
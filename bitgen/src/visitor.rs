@@ -0,0 +1,65 @@
+use crate::{dynamic, DynValue, Shape};
+
+/// Receives every value of a packed type in order, from `BitLayout::visit`. Composite values
+/// are announced before their contents and closed after them, so a visitor can keep a stack.
+///
+/// Numbers are visited decoded, a `Gray` as a `uint` and a `ZigZag` or `SignMag` as an `int`.
+/// The `name` of structs and enums is their `std::any::type_name`.
+pub trait BitVisitor {
+    fn visit_unit(&mut self) {}
+
+    fn visit_bool(&mut self, value: bool);
+
+    fn visit_uint(&mut self, width: usize, value: u128);
+
+    fn visit_int(&mut self, width: usize, value: i128);
+
+    /// Starts a struct, tuple or union, each of its `fields` is visited with `visit_field`
+    /// followed by its value.
+    fn visit_struct_begin(&mut self, _name: &'static str, _fields: usize) {}
+
+    /// Comes before the value of every field of a struct or enum variant.
+    fn visit_field(&mut self, _name: &'static str) {}
+
+    fn visit_struct_end(&mut self) {}
+
+    /// Starts the `variant` of an enum, its fields follow like the fields of a struct.
+    fn visit_enum_variant(&mut self, _name: &'static str, _variant: &'static str, _tag: u32) {}
+
+    fn visit_enum_end(&mut self) {}
+
+    /// An enum whose bits hold a `tag` without a variant, which can only be read through another
+    /// view of a union. Nothing else of the enum is visited.
+    fn visit_unknown_tag(&mut self, _name: &'static str, _tag: u32) {}
+
+    /// Starts an array, followed by its `len` elements.
+    fn visit_array(&mut self, _len: usize) {}
+
+    fn visit_array_end(&mut self) {}
+
+    /// Starts an `Option`, followed by its value if it's `Some`.
+    fn visit_option(&mut self, _is_some: bool) {}
+
+    fn visit_option_end(&mut self) {}
+
+    /// A type without a layout of its own, see `Shape::Opaque`, with its `bits` in `bytes` the
+    /// lowest bit first.
+    fn visit_opaque(&mut self, _name: &'static str, _bits: usize, _bytes: &[u8]) {}
+}
+
+/// Visits the number, `bool` or `()` with `shape` at `offset`.
+pub(crate) fn visit_leaf<V: BitVisitor + ?Sized>(
+    bytes: &[u8],
+    shape: &Shape,
+    offset: usize,
+    bits: usize,
+    visitor: &mut V,
+) {
+    match dynamic::read_leaf(bytes, shape, offset, bits) {
+        Some(DynValue::Unit) => visitor.visit_unit(),
+        Some(DynValue::Bool(value)) => visitor.visit_bool(value),
+        Some(DynValue::Uint { bits, value }) => visitor.visit_uint(bits, value),
+        Some(DynValue::Int { bits, value }) => visitor.visit_int(bits, value),
+        _ => unreachable!("only leaves are visited with `visit_leaf`"),
+    }
+}
@@ -1,153 +1,1230 @@
 use const_fnv1a_hash::fnv1a_hash_str_64;
 use proc_macro::TokenStream;
-use quote::{quote, format_ident};
+use quote::{quote, quote_spanned, format_ident};
 use syn::{parse_macro_input, DeriveInput};
 
-#[proc_macro_derive(BitType)]
-pub fn bit_type(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
-    let vis = input.vis;
-    let ident = input.ident;
-    let generics = input.generics;
-    match input.data {
-        syn::Data::Struct(data) => match data.fields {
-            syn::Fields::Named(fields) => {
-                let field_idents: Vec<_> = fields
-                    .named
-                    .iter()
-                    .map(|field| field.ident.clone().unwrap())
-                    .collect();
-                
-                let field_ident_id: Vec<_> = field_idents.iter().map(|field| fnv1a_hash_str_64(field.to_string().as_str()) as usize).collect();
-                let field_types: Vec<_> =
-                    fields.named.iter().map(|field| field.ty.clone()).collect();
-                let field_type_offsets: Vec<Vec<_>> = field_types.iter().enumerate().map(|(i, _)| field_types.iter().take(i).collect()).collect();
+/// The arguments of every `#[bitgen(...)]` attribute.
+fn bitgen_args(attrs: &[syn::Attribute]) -> Vec<syn::NestedMeta> {
+    attrs.iter()
+        .filter(|attr| attr.path.is_ident("bitgen"))
+        .filter_map(|attr| attr.parse_meta().ok())
+        .flat_map(|meta| match meta {
+            syn::Meta::List(list) => list.nested.into_iter().collect(),
+            _ => Vec::new(),
+        })
+        .collect()
+}
 
+/// Returns true if one of the attributes is `#[bitgen(<flag>)]`.
+fn has_flag(attrs: &[syn::Attribute], flag: &str) -> bool {
+    bitgen_args(attrs).iter().any(|arg| matches!(arg, syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident(flag)))
+}
 
-                quote! {
-                    #(
-                        impl #generics bitgen::TupleAccess<#field_ident_id> for #ident #generics {
-                            type Element = #field_types;
-                            const BIT_OFFSET: usize = 0#(+<#field_type_offsets as bitgen::BitType>::BITS)*;
-                        }
-                    )*
+/// The kinds of values a `#[bitgen(...)]` argument takes.
+#[derive(Clone, Copy, PartialEq)]
+enum Arg {
+    Flag,
+    Int,
+    Str,
+}
 
-                    impl #generics bitgen::BitType for #ident #generics {
-                        const BITS: usize = 0#(+<#field_types as bitgen::BitType>::BITS)*;
+const STRUCT_ARGS: &[(&str, Arg)] = &[("accessors", Arg::Flag), ("arbitrary", Arg::Flag), ("bits", Arg::Int), ("pad", Arg::Int), ("reserved", Arg::Int), ("value", Arg::Int), ("remote", Arg::Str)];
+const STRUCT_FIELD_ARGS: &[(&str, Arg)] = &[("bits", Arg::Int), ("pad", Arg::Int), ("reserved", Arg::Int), ("value", Arg::Int), ("at", Arg::Int), ("skip", Arg::Flag), ("default", Arg::Str), ("with", Arg::Str)];
+const ENUM_ARGS: &[(&str, Arg)] = &[("arbitrary", Arg::Flag), ("remote", Arg::Str), ("tag_bits", Arg::Int), ("tag_at", Arg::Str), ("variant_types", Arg::Str)];
+const VARIANT_ARGS: &[(&str, Arg)] = &[("other", Arg::Flag)];
+const UNION_ARGS: &[(&str, Arg)] = &[("accessors", Arg::Flag), ("bits", Arg::Int)];
+const UNION_FIELD_ARGS: &[(&str, Arg)] = &[("bits", Arg::Int)];
 
-                        fn from_aligned(aligned: &Self, slice: &mut [u8], mut offset: usize) {
-                            #(
-                                <#field_types as bitgen::BitType>::from_aligned(&aligned.#field_idents, &mut slice[internal::get_byte_range(offset, <#field_types as bitgen::BitType>::BITS)], offset % 8);
-                                offset += <#field_types as bitgen::BitType>::BITS;
-                            )*
-                        }
+/// The methods of `Bit` and `Access`, a generated accessor with one of these names would be hidden
+/// behind them.
+const INHERENT_METHODS: &[&str] = &["raw", "is_valid", "access", "access_mut", "access_as", "access_as_mut", "get_path", "set_path", "visit", "iter_mut", "for_each_mut"];
 
-                        fn to_aligned(slice: &[u8], mut offset: usize) -> Self { 
-                            Self {
-                                #(
-                                    #field_idents: {
-                                        let res = <#field_types as bitgen::BitType>::to_aligned(&slice[bitgen::internal::get_byte_range(offset, <#field_types as bitgen::BitType>::BITS)], offset % 8);
-                                        offset += <#field_types as bitgen::BitType>::BITS;
-                                        res
-                                    }
-                                ),*
-                            }
-                        }
-                    }
+/// Checks that the `#[bitgen(...)]` attributes only use the arguments in `allowed`, at most once each.
+fn check_args(attrs: &[syn::Attribute], allowed: &[(&str, Arg)]) -> syn::Result<()> {
+    let mut seen = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("bitgen")) {
+        let list = match attr.parse_meta()? {
+            syn::Meta::List(list) => list,
+            meta => return Err(syn::Error::new_spanned(meta, "expected `#[bitgen(...)]`")),
+        };
+        for arg in &list.nested {
+            let (path, kind) = match arg {
+                syn::NestedMeta::Meta(syn::Meta::Path(path)) => (path, Arg::Flag),
+                syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { path, lit: syn::Lit::Int(_), .. })) => (path, Arg::Int),
+                syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { path, lit: syn::Lit::Str(_), .. })) => (path, Arg::Str),
+                _ => return Err(syn::Error::new_spanned(arg, "expected `name` or `name = value`")),
+            };
+            let name = quote!(#path).to_string();
+            match allowed.iter().find(|(allowed, _)| *allowed == name) {
+                None if allowed.is_empty() => return Err(syn::Error::new_spanned(path, "`#[bitgen]` arguments aren't supported here")),
+                None => {
+                    let names: Vec<_> = allowed.iter().map(|(name, _)| format!("`{}`", name)).collect();
+                    return Err(syn::Error::new_spanned(path, format!("unknown argument `{}`, expected one of {}", name, names.join(", "))));
+                }
+                Some((_, expected)) if *expected != kind => {
+                    let message = match expected {
+                        Arg::Flag => format!("`{}` doesn't take a value", name),
+                        Arg::Int => format!("`{}` takes an integer, like `{} = 4`", name, name),
+                        Arg::Str => format!("`{}` takes a string, like `{} = \"...\"`", name, name),
+                    };
+                    return Err(syn::Error::new_spanned(arg, message));
                 }
+                Some(_) if seen.contains(&name) => return Err(syn::Error::new_spanned(path, format!("duplicate argument `{}`", name))),
+                Some(_) => seen.push(name),
             }
-            syn::Fields::Unnamed(fields) => {
-                let field_idents: Vec<_> = fields
-                    .unnamed
-                    .iter()
-                    .enumerate()
-                    .map(|(i, _)| syn::Index::from(i))
-                    .collect();
-
-                let field_types: Vec<_> =
-                    fields.unnamed.iter().map(|field| field.ty.clone()).collect();
-                let field_type_offsets: Vec<Vec<_>> = field_types.iter().enumerate().map(|(i, _)| field_types.iter().take(i).collect()).collect();
-                
-                quote! {
+        }
+    }
+    Ok(())
+}
+
+/// Checks the `#[bitgen(...)]` attributes of the whole input, and that it can derive `BitType` at all.
+fn check_input(input: &DeriveInput) -> syn::Result<()> {
+    match &input.data {
+        syn::Data::Struct(data) => {
+            check_args(&input.attrs, STRUCT_ARGS)?;
+            data.fields.iter().try_for_each(|field| check_args(&field.attrs, STRUCT_FIELD_ARGS))
+        }
+        syn::Data::Enum(data) => {
+            check_args(&input.attrs, ENUM_ARGS)?;
+            if data.variants.is_empty() {
+                return Err(syn::Error::new_spanned(&input.ident, "`BitType` can't be derived for an enum without variants"));
+            }
+            data.variants.iter().try_for_each(|variant| {
+                check_args(&variant.attrs, VARIANT_ARGS)?;
+                variant.fields.iter().try_for_each(|field| check_args(&field.attrs, &[]))
+            })
+        }
+        syn::Data::Union(data) => {
+            check_args(&input.attrs, UNION_ARGS)?;
+            data.fields.named.iter().try_for_each(|field| check_args(&field.attrs, UNION_FIELD_ARGS))
+        }
+    }
+}
+
+/// The value of `#[bitgen(<name> = <int>)]` if there is one.
+fn int_arg(attrs: &[syn::Attribute], name: &str) -> Option<syn::LitInt> {
+    bitgen_args(attrs).into_iter().find_map(|arg| match arg {
+        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { path, lit: syn::Lit::Int(lit), .. })) if path.is_ident(name) => Some(lit),
+        _ => None,
+    })
+}
+
+/// The value of `#[bitgen(<name> = "<string>")]` if there is one.
+fn str_arg(attrs: &[syn::Attribute], name: &str) -> Option<syn::LitStr> {
+    bitgen_args(attrs).into_iter().find_map(|arg| match arg {
+        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { path, lit: syn::Lit::Str(lit), .. })) if path.is_ident(name) => Some(lit),
+        _ => None,
+    })
+}
+
+/// The value a field marked with `#[bitgen(skip)]` gets when decoded, `None` if it's packed.
+fn skipped_value(field: &syn::Field) -> syn::Result<Option<quote::__private::TokenStream>> {
+    let default = str_arg(&field.attrs, "default");
+    if !has_flag(&field.attrs, "skip") {
+        return match default {
+            Some(default) => Err(syn::Error::new_spanned(default, "`default` needs `skip`")),
+            None => Ok(None),
+        };
+    }
+    for arg in ["bits", "at", "pad", "reserved"] {
+        if let Some(lit) = int_arg(&field.attrs, arg) {
+            return Err(syn::Error::new_spanned(lit, format!("a skipped field can't have `{}`", arg)));
+        }
+    }
+    if let Some(with) = str_arg(&field.attrs, "with") {
+        return Err(syn::Error::new_spanned(with, "a skipped field can't have `with`"));
+    }
+    Ok(Some(match default {
+        Some(default) => {
+            let path = default.parse::<syn::ExprPath>()?;
+            quote!(#path())
+        }
+        None => quote!(::core::default::Default::default()),
+    }))
+}
+
+/// How a field is stored in the bits.
+enum Packed {
+    /// Stored as its own type.
+    Plain(syn::Type),
+    /// A primitive integer with `#[bitgen(bits = N)]`, stored as `U<N>` or `I<N>`.
+    Narrowed { ty: syn::Type, bits: syn::LitInt, signed: bool },
+    /// A type from another crate with `#[bitgen(with = "Mirror")]`, stored as the mirror.
+    Remote { ty: syn::Type, with: syn::Type },
+}
+
+impl Packed {
+    fn new(field: &syn::Field) -> syn::Result<Self> {
+        let bits = match (int_arg(&field.attrs, "bits"), str_arg(&field.attrs, "with")) {
+            (Some(bits), None) => bits,
+            (None, Some(with)) => return Ok(Packed::Remote { ty: field.ty.clone(), with: with.parse()? }),
+            (Some(_), Some(with)) => return Err(syn::Error::new_spanned(with, "`with` and `bits` can't be combined")),
+            (None, None) => return Ok(Packed::Plain(field.ty.clone())),
+        };
+        let primitive = match &field.ty {
+            syn::Type::Path(path) if path.qself.is_none() => path.path.get_ident().map(|ident| ident.to_string()),
+            _ => None,
+        };
+        let (signed, width) = match primitive.as_deref() {
+            Some("u8") => (false, 8),
+            Some("u16") => (false, 16),
+            Some("u32") => (false, 32),
+            Some("u64") | Some("usize") => (false, 64),
+            Some("u128") => (false, 128),
+            Some("i8") => (true, 8),
+            Some("i16") => (true, 16),
+            Some("i32") => (true, 32),
+            Some("i64") | Some("isize") => (true, 64),
+            Some("i128") => (true, 128),
+            _ => return Err(syn::Error::new_spanned(&field.ty, "`#[bitgen(bits = N)]` can only be used on primitive integers")),
+        };
+        let n = bits.base10_parse::<usize>()?;
+        if n == 0 || n > width {
+            return Err(syn::Error::new_spanned(&bits, format!("a field of type `{}` can be stored in 1 to {} bits", primitive.unwrap(), width)));
+        }
+        Ok(Packed::Narrowed { ty: field.ty.clone(), bits, signed })
+    }
+
+    /// The type the field is packed as.
+    fn ty(&self) -> quote::__private::TokenStream {
+        match self {
+            Packed::Plain(ty) => quote!(#ty),
+            Packed::Narrowed { bits, signed: false, .. } => quote!(bitgen::U<#bits>),
+            Packed::Narrowed { bits, signed: true, .. } => quote!(bitgen::I<#bits>),
+            Packed::Remote { with, .. } => quote!(#with),
+        }
+    }
+
+    /// An assertion that a pointer sized field fits in its bits, the width of `usize` and `isize`
+    /// depends on the target so it can't be checked by the derive.
+    fn check(&self) -> Option<quote::__private::TokenStream> {
+        match self {
+            Packed::Narrowed { ty, bits, .. } if matches!(quote!(#ty).to_string().as_str(), "usize" | "isize") => {
+                let message = format!("a field of type `{}` can't be stored in more bits than `{}::BITS`", quote!(#ty), quote!(#ty));
+                Some(quote_spanned!(bits.span()=> assert!(#bits <= #ty::BITS as usize, #message);))
+            }
+            _ => None,
+        }
+    }
+
+    /// The packed type from the field `value`.
+    fn pack(&self, value: quote::__private::TokenStream) -> quote::__private::TokenStream {
+        match self {
+            Packed::Plain(_) => value,
+            Packed::Narrowed { bits, signed: false, .. } => quote!(bitgen::internal::narrow_unsigned::<_, #bits>(#value)),
+            Packed::Narrowed { bits, signed: true, .. } => quote!(bitgen::internal::narrow_signed::<_, #bits>(#value)),
+            Packed::Remote { ty, with } => quote!(<#with as bitgen::Remote<#ty>>::from_remote(&#value)),
+        }
+    }
+
+    /// A reference to the packed type from the field `value`.
+    fn encode(&self, value: quote::__private::TokenStream) -> quote::__private::TokenStream {
+        let packed = self.pack(value);
+        quote!(&#packed)
+    }
+
+    /// The field value from the packed `value`.
+    fn decode(&self, value: quote::__private::TokenStream) -> quote::__private::TokenStream {
+        match self {
+            Packed::Plain(_) => value,
+            Packed::Narrowed { ty, .. } => {
+                let packed = self.ty();
+                quote!(<#packed as bitgen::internal::AsPrimitive<#ty>>::as_(#value))
+            }
+            Packed::Remote { ty, with } => quote!(<#with as bitgen::Remote<#ty>>::into_remote(#value)),
+        }
+    }
+}
+
+/// Bits that don't belong to any field, from `#[bitgen(pad = N)]` or `#[bitgen(reserved = N)]`.
+struct Padding {
+    bits: syn::LitInt,
+    value: syn::LitInt,
+    /// Reserved bits are checked to still be `value` by `is_valid`.
+    checked: bool,
+}
+
+impl Padding {
+    fn new(attrs: &[syn::Attribute]) -> syn::Result<Option<Self>> {
+        let value = int_arg(attrs, "value");
+        let (bits, checked) = match (int_arg(attrs, "pad"), int_arg(attrs, "reserved")) {
+            (Some(bits), None) => (bits, false),
+            (None, Some(bits)) => (bits, true),
+            (Some(_), Some(bits)) => return Err(syn::Error::new_spanned(bits, "`pad` and `reserved` can't be combined")),
+            (None, None) => return match value {
+                Some(value) => Err(syn::Error::new_spanned(value, "`value` needs `pad` or `reserved`")),
+                None => Ok(None),
+            },
+        };
+        let n = bits.base10_parse::<u32>()?;
+        if n == 0 || n > 128 {
+            return Err(syn::Error::new_spanned(bits, "padding can be 1 to 128 bits"));
+        }
+        let value = match value {
+            Some(value) => {
+                let v = value.base10_parse::<u128>()?;
+                if n < 128 && v >> n != 0 {
+                    return Err(syn::Error::new_spanned(value, format!("the value does not fit in {} bits", n)));
+                }
+                // Drop any suffix, the value is passed as the underlying type of `U<N>`.
+                syn::LitInt::new(&v.to_string(), value.span())
+            }
+            None => syn::LitInt::new("0", bits.span()),
+        };
+        Ok(Some(Padding { bits, value, checked }))
+    }
+}
+
+/// The names of the type and const parameters.
+fn generic_params(generics: &syn::Generics) -> Vec<String> {
+    generics.params.iter().filter_map(|param| match param {
+        syn::GenericParam::Type(param) => Some(param.ident.to_string()),
+        syn::GenericParam::Const(param) => Some(param.ident.to_string()),
+        syn::GenericParam::Lifetime(_) => None,
+    }).collect()
+}
+
+/// Returns true if `tokens` mention one of `params`.
+fn mentions(tokens: quote::__private::TokenStream, params: &[String]) -> bool {
+    tokens.to_string()
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .any(|word| params.iter().any(|param| param == word))
+}
+
+/// The where clause of the generated impls. Every field type has to be `bound`, and type
+/// parameters get the size bound the tuple and array impls of `BitType` need.
+fn where_clause(generics: &syn::Generics, types: &[quote::__private::TokenStream], bound: quote::__private::TokenStream) -> quote::__private::TokenStream {
+    let mut bounded: Vec<&quote::__private::TokenStream> = Vec::new();
+    for ty in types {
+        if !bounded.iter().any(|other| other.to_string() == ty.to_string()) {
+            bounded.push(ty);
+        }
+    }
+    // Spanned so a field type that isn't `bound` is pointed out.
+    let bounded = bounded.into_iter().map(|ty| {
+        let span = syn::spanned::Spanned::span(ty);
+        let bound: quote::__private::TokenStream = bound.clone().into_iter().map(|mut token| {
+            token.set_span(span);
+            token
+        }).collect();
+        quote_spanned!(span=> #ty: #bound)
+    });
+    let sized = generics.type_params().map(|param| &param.ident);
+    let predicates = generics.where_clause.iter().flat_map(|clause| clause.predicates.iter());
+    quote! {
+        where
+            #(#predicates,)*
+            #(#bounded,)*
+            #([u8; ::core::mem::size_of::<#sized>()]: Sized,)*
+    }
+}
+
+/// A `PhantomData` using every type and lifetime parameter, for the generated types that might
+/// not use all of them.
+fn phantom(generics: &syn::Generics) -> Option<quote::__private::TokenStream> {
+    let lifetimes: Vec<_> = generics.lifetimes().map(|param| &param.lifetime).collect();
+    let types: Vec<_> = generics.type_params().map(|param| &param.ident).collect();
+    if lifetimes.is_empty() && types.is_empty() {
+        return None;
+    }
+    Some(quote!(::core::marker::PhantomData<fn() -> (#(&#lifetimes (),)* #(#types,)*)>))
+}
+
+/// The tag of every variant, its explicit discriminant or one more than the previous tag.
+fn enum_tags(variants: &[&syn::Variant]) -> syn::Result<Vec<u128>> {
+    let mut next = 0u128;
+    variants.iter().map(|variant| {
+        if let Some((_, discriminant)) = &variant.discriminant {
+            next = match discriminant {
+                syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(lit), .. }) => lit.base10_parse::<u32>()? as u128,
+                _ => return Err(syn::Error::new_spanned(discriminant, "discriminants must be integer literals that fit in a `u32`")),
+            };
+        }
+        if next > u32::MAX as u128 {
+            return Err(syn::Error::new_spanned(&variant.ident, "the tag of this variant doesn't fit in a `u32`"));
+        }
+        next += 1;
+        Ok(next - 1)
+    }).collect()
+}
+
+/// Splits off the variant marked with `#[bitgen(other)]`, which holds the tags no other variant has.
+fn other_variant(data: &syn::DataEnum) -> syn::Result<(Vec<&syn::Variant>, Option<&syn::Variant>)> {
+    let (others, variants): (Vec<_>, Vec<_>) = data.variants.iter().partition(|variant| has_flag(&variant.attrs, "other"));
+    if let Some(extra) = others.get(1) {
+        return Err(syn::Error::new_spanned(&extra.ident, "an enum can only have one `other` variant"));
+    }
+    match others.first() {
+        Some(other) if !matches!(&other.fields, syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1) => {
+            Err(syn::Error::new_spanned(other, "an `other` variant holds only the tag, like `Unknown(U<N>)`"))
+        }
+        Some(other) if variants.is_empty() => Err(syn::Error::new_spanned(&other.ident, "an enum needs a variant that isn't `other`")),
+        other => Ok((variants, other.copied())),
+    }
+}
+
+/// Evaluates `BITS` of a type without generics in a constant if it has `checks`, so they fail the
+/// build even when `BITS` is never used. Generic types are checked once they're used.
+fn evaluate_checks(ident: &syn::Ident, generics: &syn::Generics, checks: &[quote::__private::TokenStream]) -> quote::__private::TokenStream {
+    if checks.is_empty() || !generics.params.is_empty() {
+        return quote!();
+    }
+    quote!(const _: usize = <#ident as bitgen::BitType>::BITS;)
+}
+
+/// Implements `BitType` and `TupleAccess` for a struct, laying out its fields in order.
+fn derive_struct(attrs: &[syn::Attribute], ident: &syn::Ident, generics: &syn::Generics, fields: &syn::Fields) -> syn::Result<quote::__private::TokenStream> {
+    let mut members = Vec::new();
+    let mut packed_fields = Vec::new();
+    let mut skipped_members = Vec::new();
+    let mut skipped_values = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(syn::Index::from(i)),
+        };
+        match skipped_value(field)? {
+            Some(value) => {
+                skipped_members.push(member);
+                skipped_values.push(value);
+            }
+            None => {
+                members.push(member);
+                packed_fields.push(field);
+            }
+        }
+    }
+    let ids: Vec<_> = members.iter().map(|member| match member {
+        syn::Member::Named(ident) => fnv1a_hash_str_64(ident.to_string().as_str()) as usize,
+        syn::Member::Unnamed(index) => index.index as usize,
+    }).collect();
+    let packed = packed_fields.iter().copied().map(Packed::new).collect::<syn::Result<Vec<_>>>()?;
+    let field_types: Vec<_> = packed.iter().map(Packed::ty).collect();
+    let field_encoded: Vec<_> = packed.iter().zip(&members).map(|(packed, member)| packed.encode(quote!(aligned.#member))).collect();
+    let field_decoded: Vec<_> = packed.iter().map(|packed| packed.decode(quote!(res))).collect();
+
+    // Every offset is relative to the end of the previous field, which keeps the expressions short.
+    let mut end = quote!(0);
+    let mut field_offsets = Vec::new();
+    let mut paddings = Vec::new();
+    // The bits skipped to reach a field with `at`, cleared like padding.
+    let mut gaps = Vec::new();
+    let mut checks: Vec<_> = packed.iter().filter_map(Packed::check).collect();
+    for ((field, ty), (member, id)) in packed_fields.iter().zip(&field_types).zip(members.iter().zip(&ids)) {
+        if let Some(padding) = Padding::new(&field.attrs)? {
+            let bits = padding.bits.clone();
+            paddings.push((end.clone(), padding));
+            end = quote!(#end + #bits);
+        }
+        if let Some(at) = int_arg(&field.attrs, "at") {
+            let message = format!("field `{}` of `{}` overlaps the fields before it", quote!(#member), ident);
+            checks.push(quote_spanned!(at.span()=> assert!(#end <= #at, #message);));
+            gaps.push((end, at.clone()));
+            end = quote!(#at);
+        }
+        field_offsets.push(end);
+        end = quote!(<Self as bitgen::TupleAccess<#id>>::BIT_OFFSET + <#ty as bitgen::BitType>::BITS);
+    }
+    if let Some(padding) = Padding::new(attrs)? {
+        let bits = padding.bits.clone();
+        paddings.push((end.clone(), padding));
+        end = quote!(#end + #bits);
+    }
+    if let Some(total) = int_arg(attrs, "bits") {
+        let message = format!("the fields of `{}` don't add up to {} bits", ident, total);
+        checks.push(quote_spanned!(total.span()=> assert!(#end == #total, #message);));
+    }
+
+    let padding_offsets: Vec<_> = paddings.iter().map(|(offset, _)| offset).collect();
+    let padding_bits: Vec<_> = paddings.iter().map(|(_, padding)| &padding.bits).collect();
+    let padding_values: Vec<_> = paddings.iter().map(|(_, padding)| &padding.value).collect();
+    let reserved: Vec<_> = paddings.iter().filter(|(_, padding)| padding.checked).collect();
+    let reserved_offsets: Vec<_> = reserved.iter().map(|(offset, _)| offset).collect();
+    let reserved_bits: Vec<_> = reserved.iter().map(|(_, padding)| &padding.bits).collect();
+    let reserved_values: Vec<_> = reserved.iter().map(|(_, padding)| &padding.value).collect();
+    let gap_starts: Vec<_> = gaps.iter().map(|(start, _)| start).collect();
+    let gap_ends: Vec<_> = gaps.iter().map(|(_, end)| end).collect();
+
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let where_clause = where_clause(generics, &field_types, quote!(bitgen::BitType));
+    let evaluate_checks = evaluate_checks(ident, generics, &checks);
+
+    Ok(quote! {
+        #(
+            impl #impl_generics bitgen::TupleAccess<#ids> for #ident #ty_generics #where_clause {
+                type Element = #field_types;
+                const BIT_OFFSET: usize = #field_offsets;
+            }
+        )*
+
+        impl #impl_generics bitgen::BitType for #ident #ty_generics #where_clause {
+            const BITS: usize = {
+                #(#checks)*
+                #end
+            };
+
+            fn from_aligned(aligned: &Self, slice: &mut [u8], offset: usize) {
+                #(
+                    bitgen::internal::clear_bits(slice, offset + #gap_starts, #gap_ends - (#gap_starts));
+                )*
+                #(
+                    let field_offset = offset + #padding_offsets;
+                    <bitgen::U<#padding_bits> as bitgen::BitType>::from_aligned(&bitgen::U::new(#padding_values), &mut slice[bitgen::internal::get_byte_range(field_offset, #padding_bits)], field_offset % 8);
+                )*
+                #(
+                    let field_offset = offset + <Self as bitgen::TupleAccess<#ids>>::BIT_OFFSET;
+                    <#field_types as bitgen::BitType>::from_aligned(#field_encoded, &mut slice[bitgen::internal::get_byte_range(field_offset, <#field_types as bitgen::BitType>::BITS)], field_offset % 8);
+                )*
+            }
+
+            fn to_aligned(slice: &[u8], offset: usize) -> Self {
+                Self {
                     #(
-                        impl #generics bitgen::TupleAccess<#field_idents> for #ident #generics {
-                            type Element = #field_types;
-                            const BIT_OFFSET: usize = 0#(+<#field_type_offsets as bitgen::BitType>::BITS)*;
-                        }
+                        #members: {
+                            let field_offset = offset + <Self as bitgen::TupleAccess<#ids>>::BIT_OFFSET;
+                            let res = <#field_types as bitgen::BitType>::to_aligned(&slice[bitgen::internal::get_byte_range(field_offset, <#field_types as bitgen::BitType>::BITS)], field_offset % 8);
+                            #field_decoded
+                        },
                     )*
-                    impl #generics bitgen::BitType for #ident #generics {
-                        const BITS: usize = 0#(+<#field_types as bitgen::BitType>::BITS)*;
+                    #(#skipped_members: #skipped_values,)*
+                }
+            }
 
-                        fn from_aligned(aligned: &Self, slice: &mut [u8], mut offset: usize) {
-                            #(
-                                <#field_types as bitgen::BitType>::from_aligned(&aligned.#field_idents, &mut slice[bitgen::internal::get_byte_range(offset, <#field_types as bitgen::BitType>::BITS)], offset % 8);
-                                offset += <#field_types as bitgen::BitType>::BITS;
-                            )*
-                        }
+            fn is_valid(slice: &[u8], offset: usize) -> bool {
+                #(
+                    let field_offset = offset + #reserved_offsets;
+                    let res = <bitgen::U<#reserved_bits> as bitgen::BitType>::to_aligned(&slice[bitgen::internal::get_byte_range(field_offset, #reserved_bits)], field_offset % 8);
+                    if res != bitgen::U::new(#reserved_values) {
+                        return false;
+                    }
+                )*
+                #(
+                    let field_offset = offset + <Self as bitgen::TupleAccess<#ids>>::BIT_OFFSET;
+                    if !<#field_types as bitgen::BitType>::is_valid(&slice[bitgen::internal::get_byte_range(field_offset, <#field_types as bitgen::BitType>::BITS)], field_offset % 8) {
+                        return false;
+                    }
+                )*
+                true
+            }
+        }
 
-                        fn to_aligned(slice: &[u8], mut offset: usize) -> Self {
-                            Self(
-                                #(
-                                    {
-                                        let res = <#field_types as bitgen::BitType>::to_aligned(&slice[bitgen::internal::get_byte_range(offset, <#field_types as bitgen::BitType>::BITS)], offset % 8);
-                                        offset += <#field_types as bitgen::BitType>::BITS;
-                                        res
-                                    }
-                                ),*
-                            )
-                        }
+        #evaluate_checks
+    })
+}
+
+/// Implements `BitType` and `TupleAccess` for a union, every field overlays the same bits.
+fn derive_union(attrs: &[syn::Attribute], ident: &syn::Ident, generics: &syn::Generics, fields: &syn::FieldsNamed) -> syn::Result<quote::__private::TokenStream> {
+    let members: Vec<_> = fields.named.iter().map(|field| field.ident.clone().unwrap()).collect();
+    let ids: Vec<_> = members.iter().map(|member| fnv1a_hash_str_64(member.to_string().as_str()) as usize).collect();
+    let packed = fields.named.iter().map(Packed::new).collect::<syn::Result<Vec<_>>>()?;
+    let field_types: Vec<_> = packed.iter().map(Packed::ty).collect();
+
+    // The whole union is read and written as its first field, `BitUnion` promises it's the one it holds.
+    let first = &members[0];
+    let first_type = &field_types[0];
+    let first_encoded = packed[0].encode(quote!(*value));
+    let first_decoded = packed[0].decode(quote!(res));
+    let bits = field_types.iter().fold(quote!(0), |acc, ty| quote!(bitgen::internal::max(#acc, <#ty as bitgen::BitType>::BITS)));
+    let mut checks = Vec::new();
+    checks.extend(packed.iter().filter_map(Packed::check));
+    if let Some(total) = int_arg(attrs, "bits") {
+        let message = format!("the fields of `{}` aren't {} bits", ident, total);
+        checks.push(quote_spanned!(total.span()=> assert!(#bits == #total, #message);));
+    }
+
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let where_clause = where_clause(generics, &field_types, quote!(bitgen::BitType));
+    let evaluate_checks = evaluate_checks(ident, generics, &checks);
+
+    Ok(quote! {
+        #(
+            impl #impl_generics bitgen::TupleAccess<#ids> for #ident #ty_generics #where_clause {
+                type Element = #field_types;
+                const BIT_OFFSET: usize = 0;
+            }
+        )*
+
+        impl #impl_generics bitgen::BitType for #ident #ty_generics #where_clause {
+            const BITS: usize = {
+                #(#checks)*
+                #bits
+            };
+
+            fn from_aligned(aligned: &Self, slice: &mut [u8], offset: usize) {
+                bitgen::internal::assert_bit_union::<Self>();
+                // SAFETY: `BitUnion` promises that `aligned` holds the first field.
+                let value = unsafe { &aligned.#first };
+                let first_bits = <#first_type as bitgen::BitType>::BITS;
+                <#first_type as bitgen::BitType>::from_aligned(#first_encoded, &mut slice[bitgen::internal::get_byte_range(offset, first_bits)], offset);
+                // The first field can be narrower than the widest one.
+                bitgen::internal::clear_bits(slice, offset + first_bits, <Self as bitgen::BitType>::BITS - first_bits);
+            }
+
+            fn to_aligned(slice: &[u8], offset: usize) -> Self {
+                let first_bits = <#first_type as bitgen::BitType>::BITS;
+                let res = <#first_type as bitgen::BitType>::to_aligned(&slice[bitgen::internal::get_byte_range(offset, first_bits)], offset);
+                Self { #first: #first_decoded }
+            }
+
+            fn is_valid(slice: &[u8], offset: usize) -> bool {
+                <#first_type as bitgen::BitType>::is_valid(&slice[bitgen::internal::get_byte_range(offset, <#first_type as bitgen::BitType>::BITS)], offset)
+            }
+        }
+
+        #evaluate_checks
+    })
+}
+
+/// Implements a `<Type>Accessors` trait for `Bit<Type>` and its accessors, for types marked with
+/// `#[bitgen(accessors)]`. It has a getter, a setter, an accessor and a builder for every field.
+fn accessors(input: &DeriveInput) -> syn::Result<quote::__private::TokenStream> {
+    if !has_flag(&input.attrs, "accessors") {
+        return Ok(quote!());
+    }
+    let fields = match &input.data {
+        syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => &fields.named,
+        syn::Data::Union(data) => &data.fields.named,
+        _ => return Err(syn::Error::new_spanned(&input.ident, "`accessors` needs named fields")),
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(&input.generics, "`accessors` can't be used on generic types"));
+    }
+    let vis = &input.vis;
+    let ident = &input.ident;
+    let trait_ident = format_ident!("{}Accessors", ident);
+    let trait_doc = format!("Getters and setters for the fields of `{}` in `Bit<{}>` and its accessors.", ident, ident);
+
+    let mut getters = Vec::new();
+    let mut setters = Vec::new();
+    let mut accessors = Vec::new();
+    let mut builders = Vec::new();
+    let mut accessor_types = Vec::new();
+    let mut ids = Vec::new();
+    let mut types = Vec::new();
+    let mut packed_types = Vec::new();
+    let mut packs = Vec::new();
+    let mut unpacks = Vec::new();
+    let mut getter_docs = Vec::new();
+    let mut setter_docs = Vec::new();
+    let mut accessor_docs = Vec::new();
+    let mut builder_docs = Vec::new();
+    for field in fields.iter().filter(|field| !has_flag(&field.attrs, "skip")) {
+        let field_ident = field.ident.as_ref().unwrap();
+        let name = syn::ext::IdentExt::unraw(field_ident);
+        let packed = Packed::new(field)?;
+        let methods = [field_ident.clone(), format_ident!("set_{}", name), format_ident!("{}_mut", name), format_ident!("with_{}", name)];
+        if let Some(method) = methods.iter().find(|method| INHERENT_METHODS.contains(&syn::ext::IdentExt::unraw(*method).to_string().as_str())) {
+            return Err(syn::Error::new_spanned(field_ident, format!("the accessor `{}` would be hidden by the method of `Bit` with the same name, rename the field", method)));
+        }
+        let [getter, setter, accessor, builder] = methods;
+        getters.push(getter);
+        setters.push(setter);
+        accessors.push(accessor);
+        builders.push(builder);
+        accessor_types.push(format_ident!("{}Mut", name.to_string().split('_').map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        }).collect::<String>()));
+        ids.push(fnv1a_hash_str_64(name.to_string().as_str()) as usize);
+        types.push(field.ty.clone());
+        packed_types.push(packed.ty());
+        packs.push(packed.pack(quote!(value)));
+        unpacks.push(packed.decode(quote!(res)));
+        getter_docs.push(format!("The value of `{}`.", name));
+        setter_docs.push(format!("Sets `{}` to `value`.", name));
+        accessor_docs.push(format!("An accessor of `{}`.", name));
+        builder_docs.push(format!("Sets `{}` to `value` and returns `self`.", name));
+    }
+
+    Ok(quote! {
+        #[doc = #trait_doc]
+        #vis trait #trait_ident: Sized {
+            /// Whether the bits can be changed, the setters need `Mut`.
+            type Mutability: bitgen::internal::Mutability;
+            #(
+                type #accessor_types<'b> where Self: 'b;
+            )*
+            #(
+                #[doc = #getter_docs]
+                fn #getters(&self) -> #types;
+
+                #[doc = #setter_docs]
+                fn #setters(&mut self, value: #types)
+                where
+                    (Self::Mutability, bitgen::internal::Mut): bitgen::internal::InferEq;
+
+                #[doc = #accessor_docs]
+                fn #accessors(&mut self) -> Self::#accessor_types<'_>
+                where
+                    (Self::Mutability, bitgen::internal::Mut): bitgen::internal::InferEq;
+
+                #[doc = #builder_docs]
+                fn #builders(mut self, value: #types) -> Self
+                where
+                    (Self::Mutability, bitgen::internal::Mut): bitgen::internal::InferEq,
+                {
+                    self.#setters(value);
+                    self
+                }
+            )*
+        }
+
+        impl #trait_ident for bitgen::Bit<#ident> {
+            type Mutability = bitgen::internal::Mut;
+            #(
+                type #accessor_types<'b> = <bitgen::internal::Access<'b, bitgen::internal::Mut, Self, #ident, 0> as bitgen::internal::ChildAccess<#ids>>::Child;
+            )*
+            #(
+                fn #getters(&self) -> #types {
+                    let res = bitgen::Accessor::extract(&bitgen::Accessor::get::<#ids>(bitgen::Bit::access(self)));
+                    #unpacks
+                }
+
+                fn #setters(&mut self, value: #types) {
+                    bitgen::Accessor::insert(&bitgen::Accessor::get::<#ids>(bitgen::Bit::access_mut(self)), #packs)
+                }
+
+                fn #accessors(&mut self) -> Self::#accessor_types<'_> {
+                    bitgen::Accessor::get::<#ids>(bitgen::Bit::access_mut(self))
+                }
+            )*
+        }
+
+        impl<'a, M: bitgen::internal::Mutability, BC: bitgen::BitContainer, const OFFSET: usize> #trait_ident
+            for bitgen::internal::Access<'a, M, BC, #ident, OFFSET>
+        where
+            #(
+                Self: bitgen::internal::ChildAccess<#ids>,
+                <Self as bitgen::internal::ChildAccess<#ids>>::Child: bitgen::Accessor<BC, #packed_types, M, Extracted = #packed_types, InsertResult = ()>,
+            )*
+        {
+            type Mutability = M;
+            #(
+                type #accessor_types<'b> = <Self as bitgen::internal::ChildAccess<#ids>>::Child where Self: 'b;
+            )*
+            #(
+                fn #getters(&self) -> #types {
+                    let res = bitgen::Accessor::extract(&bitgen::Accessor::get::<#ids>(::core::clone::Clone::clone(self)));
+                    #unpacks
+                }
+
+                fn #setters(&mut self, value: #types)
+                where
+                    (M, bitgen::internal::Mut): bitgen::internal::InferEq,
+                {
+                    bitgen::Accessor::insert(&bitgen::Accessor::get::<#ids>(::core::clone::Clone::clone(self)), #packs)
+                }
+
+                fn #accessors(&mut self) -> Self::#accessor_types<'_>
+                where
+                    (M, bitgen::internal::Mut): bitgen::internal::InferEq,
+                {
+                    bitgen::Accessor::get::<#ids>(::core::clone::Clone::clone(self))
+                }
+            )*
+        }
+    })
+}
+
+/// Implements `Remote` for mirrors of foreign types marked with `#[bitgen(remote = "path::Type")]`,
+/// converting between them field by field.
+fn remote(input: &DeriveInput) -> syn::Result<quote::__private::TokenStream> {
+    let remote = match str_arg(&input.attrs, "remote") {
+        Some(remote) => remote.parse::<syn::Path>()?,
+        None => return Ok(quote!()),
+    };
+    // Constructors and patterns can't take generic arguments without a turbofish, leave them to inference.
+    let mut remote_ctor = remote.clone();
+    if let Some(segment) = remote_ctor.segments.last_mut() {
+        segment.arguments = syn::PathArguments::None;
+    }
+    let members = |fields: &syn::Fields| -> Vec<syn::Member> {
+        fields.iter().enumerate().map(|(i, field)| match &field.ident {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(syn::Index::from(i)),
+        }).collect()
+    };
+    let (from_remote, into_remote) = match &input.data {
+        syn::Data::Struct(data) => {
+            let members = members(&data.fields);
+            (
+                quote!(Self { #(#members: ::core::clone::Clone::clone(&remote.#members),)* }),
+                quote!(#remote_ctor { #(#members: self.#members,)* }),
+            )
+        }
+        syn::Data::Enum(data) => {
+            let variants: Vec<_> = data.variants.iter().map(|variant| &variant.ident).collect();
+            let members: Vec<_> = data.variants.iter().map(|variant| members(&variant.fields)).collect();
+            let binds: Vec<Vec<_>> = members.iter().map(|members| members.iter().map(|member| format_ident!("__bitgen_{}", quote!(#member).to_string())).collect()).collect();
+            (
+                quote! {
+                    match remote {
+                        #(#remote_ctor::#variants { #(#members: #binds,)* } => Self::#variants { #(#members: ::core::clone::Clone::clone(#binds),)* },)*
                     }
+                },
+                quote! {
+                    match self {
+                        #(Self::#variants { #(#members: #binds,)* } => #remote_ctor::#variants { #(#members: #binds,)* },)*
+                    }
+                },
+            )
+        }
+        syn::Data::Union(_) => return Err(syn::Error::new_spanned(&input.ident, "`remote` can't be used on unions")),
+    };
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics bitgen::Remote<#remote> for #ident #ty_generics #where_clause {
+            fn from_remote(remote: &#remote) -> Self {
+                #from_remote
+            }
+
+            fn into_remote(self) -> #remote {
+                #into_remote
+            }
+        }
+    })
+}
+
+/// The name of a field in its layout, the index for tuple fields.
+fn member_name(member: &syn::Member) -> String {
+    match member {
+        syn::Member::Named(ident) => syn::ext::IdentExt::unraw(ident).to_string(),
+        syn::Member::Unnamed(index) => index.index.to_string(),
+    }
+}
+
+/// Implements `BitLayout`, describing the fields the `BitType` impl packs with the offsets and tags
+/// it uses, and visiting them in the same order. Every field type has to be `BitLayout` too.
+fn layout(input: &DeriveInput) -> syn::Result<quote::__private::TokenStream> {
+    let mut packed_types = Vec::new();
+    // Builds a `FieldLayout` and a visit for every packed field, at `offset(member, packed type)`.
+    let mut field_layouts = |fields: &syn::Fields, offset: &mut dyn FnMut(&syn::Member, &quote::__private::TokenStream) -> quote::__private::TokenStream| -> syn::Result<(Vec<quote::__private::TokenStream>, Vec<quote::__private::TokenStream>)> {
+        let mut layouts = Vec::new();
+        let mut visits = Vec::new();
+        for (i, field) in fields.iter().enumerate() {
+            if has_flag(&field.attrs, "skip") {
+                continue;
+            }
+            let member = match &field.ident {
+                Some(ident) => syn::Member::Named(ident.clone()),
+                None => syn::Member::Unnamed(syn::Index::from(i)),
+            };
+            let name = member_name(&member);
+            let packed = Packed::new(field)?.ty();
+            let ty = &field.ty;
+            let offset = offset(&member, &packed);
+            layouts.push(quote! {
+                bitgen::FieldLayout {
+                    name: #name,
+                    offset: #offset,
+                    bits: <#packed as bitgen::BitType>::BITS,
+                    type_name: ::core::any::type_name::<#ty>(),
+                    layout: <#packed as bitgen::BitLayout>::layout(),
                 }
-            },
-            syn::Fields::Unit => {
+            });
+            visits.push(quote! {
+                visitor.visit_field(#name);
+                <#packed as bitgen::BitLayout>::visit(bytes, offset + #offset, visitor);
+            });
+            packed_types.push(packed);
+        }
+        Ok((layouts, visits))
+    };
+    let (shape, visit) = match &input.data {
+        syn::Data::Struct(data) => {
+            let (fields, visits) = field_layouts(&data.fields, &mut |member, _| {
+                let id = match member {
+                    syn::Member::Named(ident) => fnv1a_hash_str_64(ident.to_string().as_str()) as usize,
+                    syn::Member::Unnamed(index) => index.index as usize,
+                };
+                quote!(<Self as bitgen::TupleAccess<#id>>::BIT_OFFSET)
+            })?;
+            let len = fields.len();
+            (
+                quote!(bitgen::Shape::Struct(vec![#(#fields),*])),
+                quote! {
+                    visitor.visit_struct_begin(::core::any::type_name::<Self>(), #len);
+                    #(#visits)*
+                    visitor.visit_struct_end();
+                },
+            )
+        }
+        syn::Data::Union(data) => {
+            let (fields, visits) = field_layouts(&syn::Fields::Named(data.fields.clone()), &mut |_, _| quote!(0))?;
+            let len = fields.len();
+            (
+                quote!(bitgen::Shape::Union(vec![#(#fields),*])),
+                quote! {
+                    visitor.visit_struct_begin(::core::any::type_name::<Self>(), #len);
+                    #(#visits)*
+                    visitor.visit_struct_end();
+                },
+            )
+        }
+        syn::Data::Enum(data) => {
+            let (variants, _) = other_variant(data)?;
+            let first_id = fnv1a_hash_str_64(variants[0].ident.to_string().as_str()) as usize;
+            let mut layouts = Vec::new();
+            let mut visits = Vec::new();
+            let mut other_visit = quote!(visitor.visit_unknown_tag(::core::any::type_name::<Self>(), tag););
+            for variant in &data.variants {
+                let name = variant.ident.to_string();
+                let id = fnv1a_hash_str_64(name.as_str()) as usize;
+                let other = has_flag(&variant.attrs, "other");
+                let tag = if other {
+                    quote!(None)
+                } else {
+                    quote!(Some(<Self as bitgen::MaybeAccess<#id>>::EXPECTED))
+                };
+                // The fields of a variant follow each other from the start of its payload.
+                let mut end = quote!(<Self as bitgen::MaybeAccess<#id>>::BIT_OFFSET);
+                let (fields, field_visits) = field_layouts(&variant.fields, &mut |_, packed| {
+                    let offset = end.clone();
+                    end = quote!(#offset + <#packed as bitgen::BitType>::BITS);
+                    offset
+                })?;
+                layouts.push(quote! {
+                    bitgen::VariantLayout {
+                        name: #name,
+                        tag: #tag,
+                        fields: vec![#(#fields),*],
+                    }
+                });
+                let visit = quote! {
+                    visitor.visit_enum_variant(::core::any::type_name::<Self>(), #name, tag);
+                    #(#field_visits)*
+                    visitor.visit_enum_end();
+                };
+                if other {
+                    other_visit = visit;
+                } else {
+                    visits.push(quote! {
+                        if tag == <Self as bitgen::MaybeAccess<#id>>::EXPECTED {
+                            #visit
+                            return;
+                        }
+                    });
+                }
+            }
+            (
                 quote! {
-                    impl #generics bitgen::BitType for #ident #generics {
-                        const BITS: usize = 0;
+                    bitgen::Shape::Enum {
+                        tag_offset: <Self as bitgen::MaybeAccess<#first_id>>::TAG_OFFSET,
+                        tag_bits: <Self as bitgen::MaybeAccess<#first_id>>::TAG_BITS,
+                        variants: vec![#(#layouts),*],
+                    }
+                },
+                quote! {
+                    let tag = bitgen::internal::read_bits(
+                        bytes,
+                        offset + <Self as bitgen::MaybeAccess<#first_id>>::TAG_OFFSET,
+                        <Self as bitgen::MaybeAccess<#first_id>>::TAG_BITS,
+                    ) as u32;
+                    #(#visits)*
+                    #other_visit
+                },
+            )
+        }
+    };
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let where_clause = where_clause(&input.generics, &packed_types, quote!(bitgen::BitLayout));
+    Ok(quote! {
+        impl #impl_generics bitgen::BitLayout for #ident #ty_generics #where_clause {
+            fn layout() -> bitgen::Layout {
+                bitgen::Layout::new::<Self>(#shape)
+            }
 
-                        fn from_aligned(aligned: &Self, slice: &mut [u8], mut offset: usize) {}
-                        fn to_aligned(slice: &[u8], offset: usize) -> Self { Self }
+            fn visit<V: bitgen::BitVisitor + ?Sized>(bytes: &[u8], offset: usize, visitor: &mut V) {
+                #visit
+            }
+        }
+    })
+}
+
+/// A strategy generating every field of `fields` with `any`, mapped into `constructor`.
+fn arbitrary_strategy(constructor: quote::__private::TokenStream, fields: &syn::Fields) -> quote::__private::TokenStream {
+    let mut strategies = Vec::new();
+    let mut members = Vec::new();
+    let mut values = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        members.push(match &field.ident {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(syn::Index::from(i)),
+        });
+        if let Ok(Some(value)) = skipped_value(field) {
+            values.push(value);
+            continue;
+        }
+        let bind = format_ident!("field_{}", strategies.len());
+        values.push(quote!(#bind));
+        strategies.push(match Packed::new(field) {
+            Ok(packed @ (Packed::Narrowed { .. } | Packed::Remote { .. })) => {
+                let packed_ty = packed.ty();
+                let decoded = packed.decode(quote!(res));
+                quote!(any::<#packed_ty>().prop_map(|res| #decoded))
+            }
+            _ => {
+                let ty = &field.ty;
+                quote!(any::<#ty>())
+            }
+        });
+    }
+    let binds: Vec<_> = (0..strategies.len()).map(|i| format_ident!("field_{}", i)).collect();
+    // Nest the strategies in pairs so there is no limit on the amount of fields.
+    let strategy = strategies.iter().rev().fold(quote!(Just(())), |acc, strategy| quote!((#strategy, #acc)));
+    let pattern = binds.iter().rev().fold(quote!(()), |acc, bind| quote!((#bind, #acc)));
+    quote!(#strategy.prop_map(|#pattern| #constructor { #(#members: #values),* }))
+}
+
+/// Implements `proptest::arbitrary::Arbitrary` for types marked with `#[bitgen(arbitrary)]`.
+fn arbitrary(input: &DeriveInput) -> quote::__private::TokenStream {
+    if !has_flag(&input.attrs, "arbitrary") {
+        return quote!();
+    }
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let fields: Vec<_> = match &input.data {
+        syn::Data::Struct(data) => data.fields.iter().collect(),
+        syn::Data::Enum(data) => data.variants.iter().flat_map(|variant| variant.fields.iter()).collect(),
+        syn::Data::Union(_) => Vec::new(),
+    };
+    let params = generic_params(&input.generics);
+    let types = fields.iter()
+        .filter(|field| !has_flag(&field.attrs, "skip"))
+        .map(|field| &field.ty)
+        .filter(|ty| mentions(quote!(#ty), &params));
+    let predicates = input.generics.where_clause.iter().flat_map(|clause| clause.predicates.iter());
+    let strategy = match &input.data {
+        syn::Data::Struct(data) => {
+            let strategy = arbitrary_strategy(quote!(Self), &data.fields);
+            quote!(#strategy.boxed())
+        }
+        syn::Data::Enum(data) => {
+            let strategies = data.variants.iter().map(|variant| {
+                let variant_ident = &variant.ident;
+                match variant.fields.iter().next() {
+                    // Only generate the tags that decode to the `other` variant.
+                    Some(field) if has_flag(&variant.attrs, "other") => {
+                        let id = fnv1a_hash_str_64(variant_ident.to_string().as_str()) as usize;
+                        let ty = &field.ty;
+                        quote! {
+                            any::<#ty>()
+                                .prop_filter("tag of another variant", |tag| <Self as bitgen::MaybeAccess<#id>>::matches(<#ty as bitgen::internal::AsPrimitive<u32>>::as_(*tag)))
+                                .prop_map(Self::#variant_ident)
+                        }
                     }
+                    _ => arbitrary_strategy(quote!(Self::#variant_ident), &variant.fields),
                 }
+            });
+            quote!(Union::new(vec![#(#strategies.boxed()),*]).boxed())
+        }
+        syn::Data::Union(_) => return quote!(),
+    };
+    quote! {
+        impl #impl_generics bitgen::internal::proptest::arbitrary::Arbitrary for #ident #ty_generics
+        where
+            #(#predicates,)*
+            #(#types: bitgen::internal::proptest::arbitrary::Arbitrary,)*
+            Self: ::core::fmt::Debug + 'static,
+        {
+            type Parameters = ();
+            type Strategy = bitgen::internal::proptest::strategy::BoxedStrategy<Self>;
+
+            fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                use bitgen::internal::proptest::{arbitrary::any, strategy::{Just, Strategy, Union}};
+                #strategy
             }
+        }
+    }
+}
+
+/// Derives `BitType`, packing the fields of a struct or enum one after another, or overlaying the
+/// fields of a union.
+///
+/// Generic types are supported, the impls require every field type using a generic parameter to
+/// be `BitType`.
+///
+/// `BitLayout` is implemented too, describing the packed fields, their offsets and the tags of
+/// enum variants at runtime, and walking them with a `BitVisitor`. It needs every field type to
+/// be `BitLayout`, a field type with a hand-written `BitType` impl can add an empty
+/// `impl BitLayout for T {}` to show up as `Shape::Opaque`.
+///
+/// # Attributes
+/// - `#[bitgen(bits = N)]` on a primitive integer field of a struct stores it in N bits, it's
+///   accessed as `U<N>` or `I<N>`. On a struct it asserts the struct takes exactly N bits.
+/// - `#[bitgen(pad = N)]` on a struct field inserts N unused bits before it, on the struct
+///   they're inserted after the last field. `value = V` sets what's written to them, `0` by
+///   default. `#[bitgen(reserved = N)]` is the same but `BitType::is_valid` is false when the
+///   reserved bits aren't `V`, decoding doesn't check them.
+/// - `#[bitgen(at = N)]` on a struct field places it N bits from the start of the struct. The
+///   fields before it can't go past N, and the bits skipped to reach it are written as 0 like
+///   padding.
+/// - `#[bitgen(skip)]` on a struct field leaves it out of the bits, it's decoded as
+///   `Default::default()` or as `path()` with `#[bitgen(skip, default = "path")]`.
+/// - Enum variants are tagged with their discriminant. `#[bitgen(tag_bits = N)]` on an enum makes
+///   the tag N bits wide, `#[bitgen(tag_at = "end")]` puts it after the variant's fields.
+/// - The fields of every enum variant are also put in a struct, named `Enum_Variant` by default,
+///   which is what `?Variant` accesses. `#[bitgen(variant_types = "Variant{}")]` on an enum names
+///   them by replacing `{}` with the variant name.
+/// - `#[bitgen(other)]` on a variant like `Unknown(U<N>)`, where N is the tag width, decodes every
+///   tag without a variant of its own into it.
+/// - A union takes as many bits as its widest field, and every field is a view of the same bits
+///   from the start. The whole union is read and written as its first field, the bits past it are
+///   written as 0. Reading it needs an `unsafe impl BitUnion`, promising that every value that gets
+///   packed is built from the first field; write the other fields through their accessors.
+///   `bits = N` works like on a struct.
+/// - `#[bitgen(accessors)]` on a struct with named fields or a union also generates a
+///   `<Type>Accessors` trait for `Bit<Type>` and its accessors, with `field()`, `set_field(v)`,
+///   `field_mut()` and `with_field(v)` for every field that isn't skipped. A field whose accessors
+///   would be named like a method of `Bit`, like `raw` or `path` for `set_path`, is an error.
+/// - `#[bitgen(remote = "path::Type")]` on a mirror of a struct or enum from another crate
+///   implements `Remote<path::Type>`, converting between them. A field of the foreign type marked
+///   with `#[bitgen(with = "Mirror")]` is then stored as the mirror. Like with serde's remote
+///   derive, the fields of the foreign type have to be public.
+/// - `#[bitgen(arbitrary)]` also implements `proptest::arbitrary::Arbitrary`, generating every
+///   field and every enum variant. Requires the `proptest` feature. It's opt-in because the derive
+///   can't tell which features `bitgen` was built with, and because every field type has to
+///   implement `Arbitrary` as well, which a field with a hand-written `BitType` may not.
+#[proc_macro_derive(BitType, attributes(bitgen))]
+pub fn bit_type(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    if let Err(err) = check_input(&input) {
+        return err.to_compile_error().into();
+    }
+    let arbitrary = arbitrary(&input);
+    let accessors = match accessors(&input) {
+        Ok(accessors) => accessors,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let remote = match remote(&input) {
+        Ok(remote) => remote,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let layout = match layout(&input) {
+        Ok(layout) => layout,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let vis = input.vis;
+    let ident = input.ident;
+    let generics = input.generics;
+    let implementation = match input.data {
+        syn::Data::Struct(data) => match derive_struct(&input.attrs, &ident, &generics, &data.fields) {
+            Ok(implementation) => implementation,
+            Err(err) => return err.to_compile_error().into(),
         },
         syn::Data::Enum(data) => {
-            let num_variants = data.variants.len();
-            if num_variants == 0 {
-                panic!("Cannot implement on an enum with no variants");
+            let (variants, other) = match other_variant(&data) {
+                Ok(variants) => variants,
+                Err(err) => return err.to_compile_error().into(),
+            };
+            let tags = match enum_tags(&variants) {
+                Ok(tags) => tags,
+                Err(err) => return err.to_compile_error().into(),
+            };
+            let max_tag = tags.iter().copied().max().unwrap();
+            let required_bits = 128 - max_tag.leading_zeros() as usize;
+            let bits_to_represent = match int_arg(&input.attrs, "tag_bits") {
+                Some(lit) => match lit.base10_parse::<usize>() {
+                    Ok(bits) if bits < required_bits || bits > 32 => {
+                        let message = format!("the tag needs 1 to 32 bits and at least {} to fit {}", required_bits, max_tag);
+                        return syn::Error::new_spanned(lit, message).to_compile_error().into();
+                    }
+                    Ok(bits) => bits,
+                    Err(err) => return err.to_compile_error().into(),
+                },
+                None => required_bits,
+            };
+            if let Some(other) = other {
+                if tags.len() as u128 >= 1 << bits_to_represent {
+                    let message = format!("every tag is taken, `tag_bits` needs to be larger than {} to leave some to `{}`", bits_to_represent, other.ident);
+                    return syn::Error::new_spanned(&other.ident, message).to_compile_error().into();
+                }
             }
-            let bits_to_represent = if num_variants == 0 { 0 } else { std::mem::size_of::<usize>() * 8 - (num_variants - 1).leading_zeros() as usize };
+            let tag_at_end = match str_arg(&input.attrs, "tag_at") {
+                Some(lit) if lit.value() == "end" => true,
+                Some(lit) if lit.value() == "start" => false,
+                Some(lit) => return syn::Error::new_spanned(lit, "`tag_at` must be \"start\" or \"end\"").to_compile_error().into(),
+                None => false,
+            };
+            // Offsets of the tag and of the payload, relative to the start of the enum.
+            let (tag_offset, payload_offset) = if tag_at_end {
+                (quote!(<Self as bitgen::BitType>::BITS - #bits_to_represent), quote!(0))
+            } else {
+                (quote!(0), quote!(#bits_to_represent))
+            };
+            let tag_literal = |i: usize| syn::LitInt::new(&tags[i].to_string(), variants[i].ident.span());
 
-            let variant_fields: Vec<_> = data.variants.iter()
-                .filter_map(|variant| {
-                    match &variant.fields {
-                        syn::Fields::Named(fields) => Some(quote!{#fields}),
-                        syn::Fields::Unnamed(fields) => Some(quote!{#fields;}),
-                        syn::Fields::Unit => None,
+            let (impl_generics, ty_generics, _) = generics.split_for_impl();
+            let where_clause = where_clause(&generics, &data.variants.iter().flat_map(|variant| variant.fields.iter().map(|field| {
+                let ty = &field.ty;
+                quote!(#ty)
+            })).collect::<Vec<_>>(), quote!(bitgen::BitType));
+            let variant_types = match str_arg(&input.attrs, "variant_types") {
+                Some(lit) if syn::parse_str::<syn::Ident>(&lit.value().replace("{}", "Variant")).is_err() || !lit.value().contains("{}") => {
+                    return syn::Error::new_spanned(lit, "`variant_types` must be a type name with `{}` where the variant name goes").to_compile_error().into();
+                }
+                Some(lit) => lit.value(),
+                None => format!("{}_{{}}", ident),
+            };
+            // The name of the struct holding the fields of a variant.
+            let variant_type = |variant: &syn::Ident| syn::Ident::new(&variant_types.replace("{}", &variant.to_string()), variant.span());
+            let marker = phantom(&generics);
+            // The fields of a variant struct, as visible as the enum, with a marker when there
+            // are generics it might not use.
+            let struct_fields = |fields: &syn::Fields| {
+                let attrs: Vec<Vec<_>> = fields.iter().map(|field| field.attrs.iter().filter(|attr| !attr.path.is_ident("bitgen")).collect()).collect();
+                let types = fields.iter().map(|field| &field.ty);
+                let marker = marker.iter();
+                match fields {
+                    syn::Fields::Named(fields) => {
+                        let names = fields.named.iter().map(|field| &field.ident);
+                        quote!(#generics #where_clause { #(#(#attrs)* #vis #names: #types,)* #(_marker: #marker)* })
                     }
-                }).collect();
+                    _ => quote!(#generics (#(#(#attrs)* #vis #types,)* #(#marker)*) #where_clause;),
+                }
+            };
+            // Initializes the marker of a variant struct.
+            let marker_init = |fields: &syn::Fields| match (fields, &marker) {
+                (syn::Fields::Named(_), Some(_)) => quote!(_marker: ::core::marker::PhantomData,),
+                (fields, Some(_)) => {
+                    let index = syn::Index::from(fields.len());
+                    quote!(#index: ::core::marker::PhantomData,)
+                }
+                (_, None) => quote!(),
+            };
+            let variant_fields: Vec<_> = variants.iter()
+                .filter(|variant| !matches!(variant.fields, syn::Fields::Unit))
+                .map(|variant| struct_fields(&variant.fields))
+                .collect();
+            let variant_markers: Vec<_> = variants.iter()
+                .filter(|variant| !matches!(variant.fields, syn::Fields::Unit))
+                .map(|variant| marker_init(&variant.fields))
+                .collect();
 
-            let unit_idents: Vec<_> = data.variants.iter()
+            let unit_idents: Vec<_> = variants.iter()
                 .filter(|variant| matches!(variant.fields, syn::Fields::Unit))
                 .map(|variant| variant.ident.clone()).collect();
 
             let unit_ident_ids: Vec<_> = unit_idents.iter().map(|ident| fnv1a_hash_str_64(ident.to_string().as_str()) as usize).collect();
                 
-            let unit_idents_index: Vec<quote::__private::TokenStream> = data.variants.iter()
+            let unit_idents_index: Vec<_> = variants.iter()
                 .enumerate()
                 .filter(|(_, variant)| matches!(variant.fields, syn::Fields::Unit))
-                .map(|(i, _)| proc_macro::Literal::usize_unsuffixed(i).to_string().parse::<TokenStream>().unwrap().into()).collect();
+                .map(|(i, _)| tag_literal(i)).collect();
 
-            let idents: Vec<_> = data.variants.iter()
+            let idents: Vec<_> = variants.iter()
                 .filter(|variant| matches!(variant.fields, syn::Fields::Unnamed(_) | syn::Fields::Named(_)))
                 .map(|variant| variant.ident.clone()).collect();
             let ident_ids: Vec<_> = idents.iter().map(|ident| fnv1a_hash_str_64(ident.to_string().as_str()) as usize).collect();
                 
-            let idents_index: Vec<quote::__private::TokenStream> = data.variants.iter()
+            let idents_index: Vec<_> = variants.iter()
                 .enumerate()
                 .filter(|(_, variant)| matches!(variant.fields, syn::Fields::Unnamed(_) | syn::Fields::Named(_)))
-                .map(|(i, _)| proc_macro::Literal::usize_unsuffixed(i).to_string().parse::<TokenStream>().unwrap().into()).collect();
+                .map(|(i, _)| tag_literal(i)).collect();
 
-            let field_idents: Vec<Vec<_>> = data.variants.iter()
+            let field_idents: Vec<Vec<_>> = variants.iter()
                 .filter_map(|variant| 
                     match &variant.fields {
                         syn::Fields::Named(fields) => Some(fields.named.iter().map(|field| syn::Member::Named(field.ident.clone().unwrap())).collect()),
@@ -155,7 +1232,7 @@ pub fn bit_type(input: TokenStream) -> TokenStream {
                         syn::Fields::Unit => None,
                     }).collect();
             
-            let field_types: Vec<Vec<_>> = data.variants.iter()
+            let field_types: Vec<Vec<_>> = variants.iter()
                 .filter_map(|variant| 
                     match &variant.fields {
                         syn::Fields::Named(fields) => Some(fields.named.iter().map(|field| field.ty.clone()).collect()),
@@ -163,15 +1240,14 @@ pub fn bit_type(input: TokenStream) -> TokenStream {
                         syn::Fields::Unit => None,
                     }).collect();
            
-            let uuid = uuid::Uuid::new_v4();
-            let unique_wrapper_ident = format_ident!("Wrap{}{}", ident, uuid.to_string().replace('-', ""));
-            let unique_idents = idents.iter().map(|id| format_ident!("{}{}{}", ident, id, uuid.to_string().replace('-', "")));
+            let unique_idents = idents.iter().map(variant_type);
+            let payload_bits = field_types.iter().fold(quote!(0), |acc, types| quote!(bitgen::internal::max(#acc, 0 #(+ <#types as bitgen::BitType>::BITS)*)));
 
-            let captured_field_idents: Vec<Vec<_>> = data.variants.iter()
+            let captured_field_idents: Vec<Vec<_>> = variants.iter()
                 .filter_map(|variant| 
                     match &variant.fields {
-                        syn::Fields::Named(fields) => Some(fields.named.iter().map(|field| format_ident!("t_{}_{}", syn::Member::Named(field.ident.clone().unwrap()), uuid.to_string().replace('-', "_"))).collect()),
-                        syn::Fields::Unnamed(fields) => Some(fields.unnamed.iter().enumerate().map(|(i, _)| format_ident!("t_{}_{}", syn::Member::Unnamed(syn::Index::from(i)), uuid.to_string().replace('-', "_"))).collect()),
+                        syn::Fields::Named(fields) => Some(fields.named.iter().map(|field| format_ident!("__bitgen_{}", field.ident.clone().unwrap())).collect()),
+                        syn::Fields::Unnamed(fields) => Some(fields.unnamed.iter().enumerate().map(|(i, _)| format_ident!("__bitgen_{}", i)).collect()),
                         syn::Fields::Unit => None,
                     }).collect();
 
@@ -183,14 +1259,67 @@ pub fn bit_type(input: TokenStream) -> TokenStream {
                 types.iter().take(i).collect()
             }).collect()).collect();
 
-            let implementation = if num_variants == 1 {
+            let all_tags: Vec<_> = (0..tags.len()).map(tag_literal).collect();
+            let (other_encode, other_decode, other_impl) = match other {
+                Some(other) => {
+                    let other_ident = &other.ident;
+                    let other_id = fnv1a_hash_str_64(other_ident.to_string().as_str()) as usize;
+                    let other_fields = struct_fields(&other.fields);
+                    let other_marker = marker_init(&other.fields);
+                    let other_type = &other.fields.iter().next().unwrap().ty;
+                    let unique_other = variant_type(other_ident);
+                    let unassigned = (0..).find(|tag| !tags.contains(tag)).unwrap() as u32;
+                    let message = format!("`{}::{}` holds a tag that belongs to another variant", ident, other_ident);
+                    (
+                        quote! {
+                            Self::#other_ident(tag) => {
+                                #[cfg(debug_assertions)]
+                                assert!(![#(#all_tags),*].contains(&tag.extract_underlying()), #message);
+                                bitgen::U::<#bits_to_represent>::from_aligned(tag, &mut slice[bitgen::internal::get_byte_range(tag_offset, #bits_to_represent)], tag_offset % 8);
+                            },
+                        },
+                        quote!(_ => Self::#other_ident(underlying),),
+                        quote! {
+                            #[allow(non_camel_case_types)]
+                            #vis struct #unique_other #other_fields
+                            impl #impl_generics bitgen::TupleAccess<0> for #unique_other #ty_generics #where_clause {
+                                type Element = #other_type;
+                                const BIT_OFFSET: usize = 0;
+                            }
+                            impl #impl_generics bitgen::MaybeAccess<#other_id> for #ident #ty_generics #where_clause {
+                                type Element = #unique_other #ty_generics;
+                                // The tag is the element.
+                                const BIT_OFFSET: usize = #tag_offset;
+                                const TAG_OFFSET: usize = #tag_offset;
+                                const TAG_BITS: usize = #bits_to_represent;
+                                const EXPECTED: u32 = #unassigned;
+
+                                fn matches(tag: u32) -> bool {
+                                    ![#(#all_tags),*].contains(&tag)
+                                }
+                            }
+                            impl #impl_generics bitgen::BitType for #unique_other #ty_generics #where_clause {
+                                const BITS: usize = <#other_type as bitgen::BitType>::BITS;
+
+                                fn from_aligned(aligned: &Self, slice: &mut [u8], offset: usize) {
+                                    <#other_type as bitgen::BitType>::from_aligned(&aligned.0, slice, offset);
+                                }
+
+                                fn to_aligned(slice: &[u8], offset: usize) -> Self {
+                                    Self { 0: <#other_type as bitgen::BitType>::to_aligned(slice, offset), #other_marker }
+                                }
+                            }
+                        },
+                    )
+                }
+                None => (quote!(), quote!(_ => unreachable!(),), quote!()),
+            };
+            let other_valid = if other.is_some() { quote!(_ => true,) } else { quote!(_ => false,) };
+
+            let implementation = if bits_to_represent == 0 {
                 quote! {
-                    impl #generics bitgen::BitType for #ident #generics {
-                        const BITS: usize = #unique_wrapper_ident(0)#(
-                            .max(0#(
-                                + <#field_types as bitgen::BitType>::BITS
-                            )*)
-                        )*.0;
+                    impl #impl_generics bitgen::BitType for #ident #ty_generics #where_clause {
+                        const BITS: usize = #payload_bits;
 
                         fn from_aligned(aligned: &Self, slice: &mut [u8], mut offset: usize) {
                             #(
@@ -219,42 +1348,48 @@ pub fn bit_type(input: TokenStream) -> TokenStream {
                                 }
                             )*
                         }
+
+                        fn is_valid(slice: &[u8], mut offset: usize) -> bool {
+                            true #(#(&& {
+                                let valid = <#field_types as bitgen::BitType>::is_valid(&slice[bitgen::internal::get_byte_range(offset, <#field_types as bitgen::BitType>::BITS)], offset % 8);
+                                offset += <#field_types as bitgen::BitType>::BITS;
+                                valid
+                            })*)*
+                        }
                     }
                 }
             } else {
                 quote! {
                     
-                    impl #generics bitgen::BitType for #ident #generics {
-                        const BITS: usize = #bits_to_represent + #unique_wrapper_ident(0)#(
-                            .max(0#(
-                                + <#field_types as bitgen::BitType>::BITS
-                            )*)
-                        )*.0;
+                    impl #impl_generics bitgen::BitType for #ident #ty_generics #where_clause {
+                        const BITS: usize = #bits_to_represent + #payload_bits;
 
-                        fn from_aligned(aligned: &Self, slice: &mut [u8], mut offset: usize) {
+                        fn from_aligned(aligned: &Self, slice: &mut [u8], offset: usize) {
+                            let tag_offset = offset + #tag_offset;
+                            let mut offset = offset + #payload_offset;
                             match &aligned {
                                 #(
                                     Self::#unit_idents => {
-                                        bitgen::U::<#bits_to_represent>::from_aligned(&bitgen::U::new(#unit_idents_index), &mut slice[bitgen::internal::get_byte_range(offset, #bits_to_represent)], offset);
+                                        bitgen::U::<#bits_to_represent>::from_aligned(&bitgen::U::new(#unit_idents_index), &mut slice[bitgen::internal::get_byte_range(tag_offset, #bits_to_represent)], tag_offset % 8);
                                     },
                                 )*
                                 #(
                                     Self::#idents { #(#field_idents: #captured_field_idents @ _,)* } => {
-                                        let range = bitgen::internal::get_byte_range(offset, #bits_to_represent);
-                                        bitgen::U::<#bits_to_represent>::from_aligned(&bitgen::U::new(#idents_index), &mut slice[range], offset);
-                                        offset += #bits_to_represent;
+                                        bitgen::U::<#bits_to_represent>::from_aligned(&bitgen::U::new(#idents_index), &mut slice[bitgen::internal::get_byte_range(tag_offset, #bits_to_represent)], tag_offset % 8);
                                         #(
                                             <#field_types as bitgen::BitType>::from_aligned(#captured_field_idents, &mut slice[bitgen::internal::get_byte_range(offset, <#field_types as BitType>::BITS)], offset % 8);
                                             offset += <#field_types as BitType>::BITS;
                                         )*
                                     },
                                 )*
+                                #other_encode
                             }
                         }
                         
-                        fn to_aligned(slice: &[u8], mut offset: usize) -> Self {
-                            let underlying = bitgen::U::<#bits_to_represent>::to_aligned(&slice[bitgen::internal::get_byte_range(offset, #bits_to_represent)], offset);
-                            offset += #bits_to_represent;
+                        fn to_aligned(slice: &[u8], offset: usize) -> Self {
+                            let tag_offset = offset + #tag_offset;
+                            let mut offset = offset + #payload_offset;
+                            let underlying = bitgen::U::<#bits_to_represent>::to_aligned(&slice[bitgen::internal::get_byte_range(tag_offset, #bits_to_represent)], tag_offset % 8);
                             match underlying.extract_underlying() {
                                 #(#unit_idents_index => Self::#unit_idents,)*
                                 #(#idents_index => Self::#idents {
@@ -264,7 +1399,22 @@ pub fn bit_type(input: TokenStream) -> TokenStream {
                                         res
                                     }), *
                                 },)*
-                                _ => unreachable!(),
+                                #other_decode
+                            }
+                        }
+
+                        fn is_valid(slice: &[u8], offset: usize) -> bool {
+                            let tag_offset = offset + #tag_offset;
+                            let mut offset = offset + #payload_offset;
+                            let underlying = bitgen::U::<#bits_to_represent>::to_aligned(&slice[bitgen::internal::get_byte_range(tag_offset, #bits_to_represent)], tag_offset % 8);
+                            match underlying.extract_underlying() {
+                                #(#unit_idents_index => true,)*
+                                #(#idents_index => true #(&& {
+                                    let valid = <#field_types as bitgen::BitType>::is_valid(&slice[bitgen::internal::get_byte_range(offset, <#field_types as bitgen::BitType>::BITS)], offset % 8);
+                                    offset += <#field_types as bitgen::BitType>::BITS;
+                                    valid
+                                })*,)*
+                                #other_valid
                             }
                         }
                     }
@@ -273,26 +1423,31 @@ pub fn bit_type(input: TokenStream) -> TokenStream {
 
             quote! {
                 #(
-                    impl #generics bitgen::MaybeAccess<#unit_ident_ids> for #ident #generics {
+                    impl #impl_generics bitgen::MaybeAccess<#unit_ident_ids> for #ident #ty_generics #where_clause {
                         type Element = ();
-                        const BIT_OFFSET: usize = #bits_to_represent;
+                        const BIT_OFFSET: usize = #payload_offset;
+                        const TAG_OFFSET: usize = #tag_offset;
+                        const TAG_BITS: usize = #bits_to_represent;
                         const EXPECTED: u32 = #unit_idents_index;
                     }
                 )*
                 #(
-                    #vis struct #unique_idents #generics #variant_fields
+                    #[allow(non_camel_case_types)]
+                    #vis struct #unique_idents #variant_fields
                     #(
-                        impl #generics bitgen::TupleAccess<#field_ident_id> for #unique_idents #generics {
+                        impl #impl_generics bitgen::TupleAccess<#field_ident_id> for #unique_idents #ty_generics #where_clause {
                             type Element = #field_types;
                             const BIT_OFFSET: usize = 0 #( + <#field_type_offsets as bitgen::BitType>::BITS)*;
                         }
                     )*
-                    impl #generics bitgen::MaybeAccess<#ident_ids> for #ident #generics {
-                        type Element = #unique_idents #generics;
-                        const BIT_OFFSET: usize = #bits_to_represent;
+                    impl #impl_generics bitgen::MaybeAccess<#ident_ids> for #ident #ty_generics #where_clause {
+                        type Element = #unique_idents #ty_generics;
+                        const BIT_OFFSET: usize = #payload_offset;
+                        const TAG_OFFSET: usize = #tag_offset;
+                        const TAG_BITS: usize = #bits_to_represent;
                         const EXPECTED: u32 = #idents_index;
                     }
-                    impl #generics bitgen::BitType for #unique_idents #generics {
+                    impl #impl_generics bitgen::BitType for #unique_idents #ty_generics #where_clause {
                         const BITS: usize = 0#(+<#field_types as bitgen::BitType>::BITS)*;
 
                         fn from_aligned(aligned: &Self, slice: &mut [u8], mut offset: usize) {
@@ -311,20 +1466,26 @@ pub fn bit_type(input: TokenStream) -> TokenStream {
                                         res
                                     },
                                 )*
+                                #variant_markers
                             }
                         }
                     }
                 )*
-                struct #unique_wrapper_ident(usize);
-                impl #unique_wrapper_ident {
-                    const fn max(&self, other: usize) -> Self {
-                        #unique_wrapper_ident([self.0, other][(self.0 < other) as usize])
-                    }
-                }
                 #implementation
+                #other_impl
             }
         },
-        syn::Data::Union(_) => todo!(),
+        syn::Data::Union(data) => match derive_union(&input.attrs, &ident, &generics, &data.fields) {
+            Ok(implementation) => implementation,
+            Err(err) => return err.to_compile_error().into(),
+        },
+    };
+    quote! {
+        #implementation
+        #layout
+        #arbitrary
+        #accessors
+        #remote
     }
     .into()
 }
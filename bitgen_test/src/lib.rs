@@ -50,6 +50,136 @@ mod tests {
         assert_eq!(b.extract_underlying(), -1);
     }
 
+    #[test]
+    fn test_bit_fmt() {
+        let b: U<12> = ubits(0x2a);
+        assert_eq!(format!("{:b}", b), "101010");
+        assert_eq!(format!("{:#b}", b), "0b000000101010");
+        assert_eq!(format!("{:#o}", b), "0o0052");
+        assert_eq!(format!("{:#x}", b), "0x02a");
+        assert_eq!(format!("{:#X}", b), "0x02A");
+        assert_eq!(format!("{:x}", b), "2a");
+
+        let b: I<6> = ibits(-3);
+        assert_eq!(format!("{:b}", b), "111101");
+        assert_eq!(format!("{:#b}", b), "0b111101");
+        assert_eq!(format!("{:#x}", b), "0x3d");
+        assert_eq!(format!("{:#b}", ibits::<6>(2)), "0b000010");
+        assert_eq!(format!("{:#b}", ibits::<8>(-1)), "0b11111111");
+    }
+
+    #[test]
+    fn test_bit_parse() {
+        use std::num::IntErrorKind;
+
+        assert_eq!("42".parse::<U<6>>(), Ok(ubits(42)));
+        assert_eq!("0b101".parse::<U<3>>(), Ok(ubits(5)));
+        assert_eq!("0o17".parse::<U<4>>(), Ok(ubits(15)));
+        assert_eq!("0xfff".parse::<U<12>>(), Ok(ubits(0xfff)));
+        assert_eq!(
+            "0x1000".parse::<U<12>>().unwrap_err().kind(),
+            &IntErrorKind::PosOverflow
+        );
+        assert_eq!(
+            "0b1111".parse::<U<3>>().unwrap_err().kind(),
+            &IntErrorKind::PosOverflow
+        );
+        assert_eq!(
+            "0x".parse::<U<3>>().unwrap_err().kind(),
+            &IntErrorKind::Empty
+        );
+        assert_eq!(
+            "0b12".parse::<U<3>>().unwrap_err().kind(),
+            &IntErrorKind::InvalidDigit
+        );
+        assert!("-1".parse::<U<3>>().is_err());
+
+        assert_eq!("-0x8".parse::<I<4>>(), Ok(ibits(-8)));
+        assert_eq!("+0b111".parse::<I<4>>(), Ok(ibits(7)));
+        assert_eq!("-3".parse::<I<6>>(), Ok(ibits(-3)));
+        assert_eq!(
+            "8".parse::<I<4>>().unwrap_err().kind(),
+            &IntErrorKind::PosOverflow
+        );
+        assert_eq!(
+            "-9".parse::<I<4>>().unwrap_err().kind(),
+            &IntErrorKind::NegOverflow
+        );
+        assert_eq!(
+            "0x-1".parse::<I<4>>().unwrap_err().kind(),
+            &IntErrorKind::InvalidDigit
+        );
+
+        // Without a sign, prefixed numbers are the two's complement bits `{:#x}` prints.
+        assert_eq!("0x1f".parse::<I<5>>(), Ok(ibits(-1)));
+        assert_eq!("0b1000".parse::<I<4>>(), Ok(ibits(-8)));
+        assert_eq!(
+            "0o77".parse::<I<5>>().unwrap_err().kind(),
+            &IntErrorKind::PosOverflow
+        );
+        for value in I::<5>::all() {
+            assert_eq!(format!("{:#b}", value).parse(), Ok(value));
+            assert_eq!(format!("{:#o}", value).parse(), Ok(value));
+            assert_eq!(format!("{:#x}", value).parse(), Ok(value));
+            assert_eq!(format!("{:#X}", value).parse(), Ok(value));
+            assert_eq!(value.to_string().parse(), Ok(value));
+        }
+        assert_eq!(format!("{:#x}", I::<64>::MIN).parse(), Ok(I::<64>::MIN));
+    }
+
+    #[test]
+    fn test_bit_iter_traits() {
+        use std::collections::HashSet;
+
+        assert_eq!(U::<4>::MIN, ubits(0));
+        assert_eq!(U::<4>::MAX, ubits(15));
+        assert_eq!(U::<64>::MAX, ubits(u64::MAX));
+        assert_eq!(I::<4>::MIN, ibits(-8));
+        assert_eq!(I::<4>::MAX, ibits(7));
+        assert_eq!(I::<8>::MIN, ibits(i8::MIN));
+        assert_eq!(I::<4>::ONE, ibits(1));
+        assert_eq!(I::<4>::ZERO, ibits(0));
+
+        let all: Vec<_> = U::<3>::all().collect();
+        assert_eq!(all.len(), 8);
+        assert_eq!(all[5], ubits(5));
+        assert_eq!((U::<4>::ZERO..U::<4>::MAX).count(), 15);
+        assert_eq!(I::<4>::all().count(), 16);
+        assert_eq!(I::<4>::all().next(), Some(ibits(-8)));
+        assert_eq!(I::<4>::all().last(), Some(ibits(7)));
+        assert_eq!((ibits::<5>(-3)..ibits(3)).count(), 6);
+
+        let set: HashSet<U<3>> = U::<3>::all().chain(U::<3>::all()).collect();
+        assert_eq!(set.len(), 8);
+        let set: HashSet<I<3>> = I::<3>::all().collect();
+        assert!(set.contains(&ibits(-4)));
+
+        assert_eq!(
+            [ubits::<6>(1), ubits(2), ubits(3)].iter().sum::<U<6>>(),
+            ubits(6)
+        );
+        assert_eq!(U::<6>::all().take(4).skip(1).product::<U<6>>(), ubits(6));
+        assert_eq!((ibits::<5>(0)..=ibits(3)).sum::<I<5>>(), ibits(6));
+        assert_eq!([ibits::<5>(3), ibits(2)].iter().product::<I<5>>(), ibits(6));
+        assert_eq!(
+            [I::<8>::new(-3), I::new(-4)].iter().copied().sum::<I<8>>(),
+            ibits(-7)
+        );
+        assert_eq!((ibits::<5>(-3)..=ibits(2)).sum::<I<5>>(), ibits(-3));
+        assert_eq!(
+            [ibits::<5>(-3), ibits(-2)].iter().product::<I<5>>(),
+            ibits(6)
+        );
+        assert_eq!(
+            [ibits::<5>(-3), ibits(4)].iter().product::<I<5>>(),
+            ibits(-12)
+        );
+
+        let read = Bit::from(ibits::<5>(-3)).access().extract();
+        let set: HashSet<I<5>> = [read, ibits(-3)].into_iter().collect();
+        assert_eq!(set.len(), 1);
+    }
+
     #[test]
     fn test_size() {
         // 4 bits so should use 1 byte
@@ -310,12 +440,1706 @@ mod tests {
         }
         assert_eq!(arr, bit!(bit_arr).extract());
 
-        for bit in bit!(mut bit_arr).iter() {
-            bit.insert(true);
-        }
+        bit!(mut bit_arr).for_each_mut(|bit| bit.insert(true));
         assert_eq!([true; 32], bit!(bit_arr).extract());
     }
 
+    #[test]
+    fn test_encodings() {
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Sensor {
+            delta: ZigZag<I<5>>,
+            position: Gray<U<4>>,
+            temperature: SignMag<6>,
+        }
+
+        let wire = |value: Sensor| {
+            let bits = Bit::from(value);
+            unsafe { bits.access_as::<(U<5>, U<4>, U<6>)>() }.extract()
+        };
+
+        let sensor = Sensor {
+            delta: ZigZag(ibits(-3)),
+            position: Gray(ubits(7)),
+            temperature: SignMag(ibits(-12)),
+        };
+        assert_eq!(wire(sensor), (ubits(5), ubits(0b0100), ubits(0b101100)));
+        let bit_sensor = Bit::from(sensor);
+        assert_eq!(bit!(bit_sensor).extract(), sensor);
+        assert_eq!(bit!(bit_sensor.delta).extract(), ZigZag(ibits(-3)));
+
+        for value in I::<5>::all() {
+            let bit_value = Bit::from(ZigZag(value));
+            assert_eq!(bit!(bit_value).extract(), ZigZag(value));
+        }
+        let mut previous = None;
+        for value in U::<4>::all() {
+            let bit_value = Bit::from(Gray(value));
+            assert_eq!(bit!(bit_value).extract(), Gray(value));
+            let raw = unsafe { bit_value.access_as::<U<4>>() }.extract();
+            if let Some(previous) = previous {
+                assert_eq!((raw ^ previous).extract_underlying().count_ones(), 1);
+            }
+            previous = Some(raw);
+        }
+        for value in I::<6>::all().skip(1) {
+            let bit_value = Bit::from(SignMag(value));
+            assert_eq!(bit!(bit_value).extract(), SignMag(value));
+        }
+        // Negative zero
+        let bit_zero = Bit::from(ubits::<6>(0b100000));
+        assert_eq!(
+            unsafe { bit_zero.access_as::<SignMag<6>>() }.extract(),
+            SignMag(ibits(0))
+        );
+    }
+
+    #[test]
+    fn test_field_bits() {
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        #[bitgen(arbitrary)]
+        struct Reading {
+            #[bitgen(bits = 12)]
+            value: u32,
+            #[bitgen(bits = 5)]
+            offset: i8,
+            valid: bool,
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Pair(#[bitgen(bits = 3)] u8, #[bitgen(bits = 3)] i64);
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Span {
+            #[bitgen(bits = 20)]
+            start: usize,
+            #[bitgen(bits = 12)]
+            delta: isize,
+        }
+
+        assert_eq!(<Reading as BitType>::BITS, 18);
+        assert_eq!(<Pair as BitType>::BITS, 6);
+        assert_eq!(<Span as BitType>::BITS, 32);
+
+        let reading = Reading {
+            value: 4000,
+            offset: -9,
+            valid: true,
+        };
+        let mut bit_reading = Bit::from(reading);
+        assert_eq!(bit!(bit_reading).extract(), reading);
+        let value: U<12> = bit!(bit_reading.value).extract();
+        assert_eq!(value, ubits(4000));
+        let offset: I<5> = bit!(bit_reading.offset).extract();
+        assert_eq!(offset, ibits(-9));
+
+        bit!(mut bit_reading.offset).insert(ibits(-16));
+        assert_eq!(bit!(bit_reading).extract().offset, -16);
+
+        let pair = Pair(7, -4);
+        let bit_pair = Bit::from(pair);
+        assert_eq!(bit!(bit_pair).extract(), pair);
+        assert_eq!(
+            unsafe { bit_pair.access_as::<(U<3>, U<3>)>() }.extract(),
+            (ubits(7), ubits(0b100))
+        );
+
+        let span = Span {
+            start: 1 << 19,
+            delta: -2048,
+        };
+        assert_eq!(Bit::from(span).access().extract(), span);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn test_field_bits_overflow() {
+        #[derive(BitType, Clone, Copy)]
+        struct Small {
+            #[bitgen(bits = 4)]
+            value: u16,
+        }
+
+        let _ = Bit::from(Small { value: 16 });
+    }
+
+    #[test]
+    fn test_layout_attributes() {
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        #[bitgen(bits = 16, pad = 1)]
+        struct Register {
+            enable: bool,
+            #[bitgen(reserved = 3, value = 0b101)]
+            mode: U<2>,
+            #[bitgen(at = 8)]
+            value: U<4>,
+            #[bitgen(pad = 2)]
+            flag: bool,
+        }
+
+        type Raw = (bool, U<3>, U<2>, U<2>, U<4>, U<2>, bool, bool);
+
+        assert_eq!(<Register as BitType>::BITS, 16);
+        assert_eq!(
+            <Register as TupleAccess<{ hash_ident("mode") }>>::BIT_OFFSET,
+            4
+        );
+        assert_eq!(
+            <Register as TupleAccess<{ hash_ident("value") }>>::BIT_OFFSET,
+            8
+        );
+        assert_eq!(
+            <Register as TupleAccess<{ hash_ident("flag") }>>::BIT_OFFSET,
+            14
+        );
+
+        let register = Register {
+            enable: true,
+            mode: ubits(2),
+            value: ubits(9),
+            flag: true,
+        };
+        let bit_register = Bit::from(register);
+        assert_eq!(bit!(bit_register).extract(), register);
+        assert_eq!(bit!(bit_register.value).extract(), ubits(9));
+        assert_eq!(
+            unsafe { bit_register.access_as::<Raw>() }.extract(),
+            (
+                true,
+                ubits(0b101),
+                ubits(2),
+                ubits(0),
+                ubits(9),
+                ubits(0),
+                true,
+                false
+            )
+        );
+
+        let mut bit_register = Bit::from(register);
+        unsafe { bit_register.access_as_mut::<U<16>>() }.insert(U::MAX);
+        bit!(mut bit_register).insert(register);
+        assert_eq!(
+            unsafe { bit_register.access_as::<Raw>() }.extract().3,
+            ubits(0)
+        );
+    }
+
+    #[test]
+    fn test_reserved_changed() {
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Register {
+            #[bitgen(reserved = 2, value = 3)]
+            value: U<6>,
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        #[bitgen(tag_bits = 2)]
+        enum Entry {
+            Empty,
+            Register(Register),
+        }
+
+        let raw = Bit::from(ubits::<8>(0b0000_0100));
+        let register = unsafe { raw.access_as::<Register>() };
+        assert!(!register.is_valid());
+        // Decoding bits from elsewhere doesn't panic, the reserved bits are dropped.
+        assert_eq!(register.extract(), Register { value: ubits(1) });
+        assert!(Bit::from(Register { value: ubits(1) }).is_valid());
+
+        let entries = Bit::from([Entry::Register(Register { value: ubits(5) }); 2]);
+        assert!(entries.is_valid());
+        assert!(bit!(entries[1]).is_valid());
+        let raw = Bit::from((ubits::<2>(1), ubits::<8>(0), ubits::<2>(2)));
+        let entry = unsafe { raw.access_as::<(Entry, U<2>)>() };
+        assert!(!entry.is_valid());
+        let raw = Bit::from((ubits::<2>(3), ubits::<10>(0)));
+        assert!(!unsafe { raw.access_as::<(Entry, U<2>)>() }.is_valid());
+        assert!(Bit::from((Entry::Empty, ubits::<2>(3))).is_valid());
+    }
+
+    #[test]
+    fn test_skipped_fields() {
+        fn unknown() -> String {
+            "unknown".to_string()
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone)]
+        #[bitgen(arbitrary)]
+        struct Entry {
+            id: U<6>,
+            #[bitgen(skip)]
+            cache: Option<u64>,
+            #[bitgen(skip, default = "unknown")]
+            name: String,
+            valid: bool,
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Pair(#[bitgen(skip)] u32, U<3>);
+
+        assert_eq!(<Entry as BitType>::BITS, 7);
+        assert_eq!(<Pair as BitType>::BITS, 3);
+        assert_eq!(
+            <Entry as TupleAccess<{ hash_ident("valid") }>>::BIT_OFFSET,
+            6
+        );
+
+        let entry = Entry {
+            id: ubits(42),
+            cache: Some(7),
+            name: "entry".to_string(),
+            valid: true,
+        };
+        let bit_entry = Bit::from(entry);
+        assert_eq!(
+            bit!(bit_entry).extract(),
+            Entry {
+                id: ubits(42),
+                cache: None,
+                name: unknown(),
+                valid: true,
+            }
+        );
+        assert!(bit!(bit_entry.valid).extract());
+
+        let bit_pair = Bit::from(Pair(9, ubits(5)));
+        assert_eq!(bit!(bit_pair).extract(), Pair(0, ubits(5)));
+        assert_eq!(bit!(bit_pair.1).extract(), ubits(5));
+    }
+
+    #[test]
+    fn test_enum_tags() {
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        #[repr(u8)]
+        #[bitgen(tag_bits = 4)]
+        enum Opcode {
+            Nop = 2,
+            Load(U<3>) = 5,
+            Store { addr: U<3> },
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        #[bitgen(tag_at = "end")]
+        enum Trailer {
+            A(U<5>),
+            B(bool),
+        }
+
+        assert_eq!(<Opcode as BitType>::BITS, 7);
+        assert_eq!(<Trailer as BitType>::BITS, 6);
+
+        let opcodes = [
+            (Opcode::Nop, (ubits(2), ubits(0))),
+            (Opcode::Load(ubits(3)), (ubits(5), ubits(3))),
+            (Opcode::Store { addr: ubits(1) }, (ubits(6), ubits(1))),
+        ];
+        for (opcode, raw) in opcodes {
+            let bit_opcode = Bit::from(opcode);
+            assert_eq!(bit!(bit_opcode).extract(), opcode);
+            assert_eq!(
+                unsafe { bit_opcode.access_as::<(U<4>, U<3>)>() }.extract(),
+                raw
+            );
+        }
+        let bit_opcode = Bit::from(Opcode::Load(ubits(3)));
+        assert_eq!(bit!(bit_opcode?Load.0).extract(), Some(ubits(3)));
+        assert_eq!(bit!(bit_opcode?Store.addr).extract(), None);
+        assert_eq!(bit!(bit_opcode?Nop).extract(), None);
+
+        let bit_trailer = Bit::from(Trailer::B(true));
+        assert_eq!(bit!(bit_trailer).extract(), Trailer::B(true));
+        assert_eq!(
+            unsafe { bit_trailer.access_as::<(bool, U<4>, bool)>() }.extract(),
+            (true, ubits(0), true)
+        );
+        assert_eq!(bit!(bit_trailer?B.0).extract(), Some(true));
+        assert_eq!(bit!(bit_trailer?A.0).extract(), None);
+
+        let bit_trailers = Bit::from([Trailer::A(ubits(17)), Trailer::B(false)]);
+        for i in 0..2 {
+            assert_eq!(bit!(bit_trailers[i]?A.0).extract().is_some(), i == 0);
+            assert_eq!(bit!(bit_trailers[i]?B.0).extract(), [None, Some(false)][i]);
+        }
+        assert_eq!(bit!(bit_trailers[0]?A.0).extract(), Some(ubits(17)));
+    }
+
+    #[test]
+    fn test_other_variant() {
+        use proptest::{arbitrary::any, test_runner::TestRunner};
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        #[bitgen(tag_bits = 3, arbitrary)]
+        enum Message {
+            Ping,
+            Data(U<4>),
+            #[bitgen(other)]
+            Unknown(U<3>),
+            Close,
+        }
+
+        assert_eq!(<Message as BitType>::BITS, 7);
+
+        // A newer schema with more variants.
+        let newer = Bit::from((ubits::<3>(5), ubits::<4>(9)));
+        let message = unsafe { newer.access_as::<Message>() };
+        assert_eq!(message.clone().extract(), Message::Unknown(ubits(5)));
+        let unknown = message.clone();
+        assert_eq!(bit!(unknown?Unknown.0).extract(), Some(ubits(5)));
+        assert_eq!(bit!(message?Data.0).extract(), None);
+
+        for tag in 0..8 {
+            let raw = Bit::from((ubits::<3>(tag), ubits::<4>(0)));
+            let message = unsafe { raw.access_as::<Message>() };
+            let value = message.clone().extract();
+            assert_eq!(bit!(message?Unknown).extract().is_some(), tag > 2);
+            assert_eq!(Bit::from(value).access().extract(), value);
+        }
+
+        let bit_messages = Bit::from([Message::Close, Message::Unknown(ubits(7))]);
+        assert_eq!(bit!(bit_messages[0]?Unknown.0).extract(), None);
+        assert_eq!(bit!(bit_messages[1]?Unknown.0).extract(), Some(ubits(7)));
+        assert_eq!(bit!(bit_messages[1]?Close).extract(), None);
+
+        TestRunner::default()
+            .run(&any::<Message>(), |message| {
+                assert_eq!(Bit::from(message).access().extract(), message);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn test_other_variant_taken_tag() {
+        #[derive(BitType, Clone, Copy)]
+        #[bitgen(tag_bits = 2)]
+        enum Message {
+            Ping,
+            #[bitgen(other)]
+            Unknown(U<2>),
+        }
+
+        let _ = Bit::from(Message::Unknown(ubits(0)));
+    }
+
+    #[test]
+    fn test_generics() {
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        #[bitgen(arbitrary)]
+        struct Pair<T>(T, T);
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Buf<const N: usize>([U<4>; N]);
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Flagged<T: Copy, const N: usize>
+        where
+            T: PartialEq,
+        {
+            flags: [bool; N],
+            value: T,
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        enum Either<L, R> {
+            Left(L),
+            Right { value: R },
+            Neither,
+        }
+
+        assert_eq!(<Pair<U<3>> as BitType>::BITS, 6);
+        assert_eq!(<Buf<3> as BitType>::BITS, 12);
+        assert_eq!(<Flagged<U<5>, 2> as BitType>::BITS, 7);
+        assert_eq!(<Either<bool, U<5>> as BitType>::BITS, 7);
+
+        let bit_pair = Bit::from(Pair(ubits::<3>(1), ubits(6)));
+        assert_eq!(bit!(bit_pair).extract(), Pair(ubits(1), ubits(6)));
+        assert_eq!(bit!(bit_pair.1).extract(), ubits(6));
+
+        let buf = Buf([ubits(1), ubits(2), ubits(15)]);
+        let bit_buf = Bit::from(buf);
+        assert_eq!(bit!(bit_buf).extract(), buf);
+        assert_eq!(bit!(bit_buf.0[2]).extract(), ubits(15));
+
+        let flagged = Flagged {
+            flags: [true, false],
+            value: ubits::<5>(20),
+        };
+        let bit_flagged = Bit::from(flagged);
+        assert_eq!(bit!(bit_flagged).extract(), flagged);
+        assert_eq!(bit!(bit_flagged.value).extract(), ubits(20));
+
+        let either = Either::<bool, U<5>>::Right { value: ubits(17) };
+        let bit_either = Bit::from(either);
+        assert_eq!(bit!(bit_either).extract(), either);
+        assert_eq!(bit!(bit_either?Right.value).extract(), Some(ubits(17)));
+        assert_eq!(bit!(bit_either?Left.0).extract(), None);
+
+        proptest::test_runner::TestRunner::default()
+            .run(&proptest::arbitrary::any::<Pair<I<4>>>(), |pair| {
+                assert_eq!(Bit::from(pair).access().extract(), pair);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_variant_types() {
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        enum Shape {
+            Dot,
+            Line(U<4>),
+            Rect { w: U<3>, h: U<3> },
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        #[bitgen(variant_types = "{}Event")]
+        enum Event {
+            Key(U<7>),
+            Click { x: U<4>, y: U<4> },
+        }
+
+        fn line_length(line: Option<Shape_Line>) -> Option<U<4>> {
+            line.map(|line| line.0)
+        }
+
+        fn click_x(click: Option<ClickEvent>) -> Option<U<4>> {
+            click.map(|click| click.x)
+        }
+
+        let bit_shape = Bit::from(Shape::Line(ubits(9)));
+        assert_eq!(line_length(bit!(bit_shape?Line).extract()), Some(ubits(9)));
+        let rect: Option<Shape_Rect> = bit!(bit_shape?Rect).extract();
+        assert!(rect.is_none());
+
+        let bit_event = Bit::from(Event::Click {
+            x: ubits(3),
+            y: ubits(12),
+        });
+        assert_eq!(click_x(bit!(bit_event?Click).extract()), Some(ubits(3)));
+        let key: Option<KeyEvent> = bit!(bit_event?Key).extract();
+        assert!(key.is_none());
+    }
+
+    #[test]
+    fn test_union() {
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Status {
+            ready: bool,
+            error: bool,
+            code: U<6>,
+        }
+
+        // The other fields are only read through their accessors.
+        #[allow(dead_code)]
+        #[derive(BitType, Clone, Copy)]
+        #[bitgen(bits = 8)]
+        union Register {
+            raw: U<8>,
+            status: Status,
+            #[bitgen(bits = 3)]
+            low: u8,
+        }
+
+        // Every `Register` below is built from `raw`.
+        unsafe impl BitUnion for Register {}
+
+        assert_eq!(Register::BITS, 8);
+
+        let mut bit_register = Bit::from(Register { raw: ubits(0) });
+        bit!(mut bit_register.status).insert(Status {
+            ready: true,
+            error: false,
+            code: ubits(0b101010),
+        });
+        let raw = bit!(bit_register.raw);
+        assert_eq!(raw.extract(), ubits(0b1010_1001));
+        let low = bit!(bit_register.low);
+        assert_eq!(low.extract(), ubits(0b001));
+
+        bit!(mut bit_register.raw).insert(ubits(0b0000_0110));
+        let status = bit!(bit_register.status);
+        assert_eq!(
+            status.extract(),
+            Status {
+                ready: false,
+                error: true,
+                code: ubits(1),
+            }
+        );
+        assert_eq!(
+            unsafe { bit_register.access().extract().raw },
+            ubits(0b0000_0110)
+        );
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Halves {
+            low: U<4>,
+            high: U<4>,
+        }
+
+        #[allow(dead_code)]
+        #[derive(BitType, Clone, Copy)]
+        union Split {
+            low: U<4>,
+            halves: Halves,
+        }
+
+        // Every `Split` below is built from `low`.
+        unsafe impl BitUnion for Split {}
+
+        assert_eq!(Split::BITS, 8);
+        let mut bit_split = Bit::from(Split { low: ubits(9) });
+        bit!(mut bit_split.halves.high).insert(ubits(15));
+        bit!(mut bit_split).insert(Split { low: ubits(3) });
+        assert_eq!(
+            bit!(bit_split.halves).extract(),
+            Halves {
+                low: ubits(3),
+                high: ubits(0),
+            }
+        );
+    }
+
+    #[test]
+    fn test_accessors() {
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        #[bitgen(accessors)]
+        struct Header {
+            version: U<3>,
+            urgent: bool,
+            #[bitgen(bits = 12)]
+            length: u16,
+            #[bitgen(skip)]
+            cached: bool,
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        #[bitgen(accessors)]
+        struct Frame {
+            header: Header,
+            payload: [bool; 4],
+        }
+
+        let mut bit_header = Bit::from(Header {
+            version: ubits(2),
+            urgent: false,
+            length: 100,
+            cached: true,
+        });
+        assert_eq!(bit_header.version(), ubits(2));
+        assert!(!bit_header.urgent());
+        assert_eq!(bit_header.length(), 100);
+
+        bit_header.set_urgent(true);
+        bit_header.length_mut().insert(ubits(4000));
+        let bit_header = bit_header.with_version(ubits(7));
+        assert_eq!(
+            bit_header.access().extract(),
+            Header {
+                version: ubits(7),
+                urgent: true,
+                length: 4000,
+                cached: false,
+            }
+        );
+
+        let mut bit_frame = Bit::from(Frame {
+            header: bit_header.access().extract(),
+            payload: [false; 4],
+        });
+        assert_eq!(bit_frame.header().length, 4000);
+        bit_frame.header_mut().set_length(12);
+        bit_frame.set_payload([true, false, true, false]);
+        let header = bit!(bit_frame.header);
+        assert_eq!(header.length(), 12);
+        assert_eq!(header.version(), ubits(7));
+        assert_eq!(bit_frame.payload(), [true, false, true, false]);
+    }
+
+    #[test]
+    fn test_remote() {
+        mod shared {
+            #[derive(PartialEq, Debug, Clone, Copy)]
+            pub struct Color {
+                pub r: u8,
+                pub g: u8,
+                pub b: u8,
+            }
+
+            #[derive(PartialEq, Debug, Clone, Copy)]
+            pub enum Brush {
+                Eraser,
+                Pen(u8),
+                Spray { radius: u8, density: u8 },
+            }
+        }
+
+        #[derive(BitType)]
+        #[bitgen(remote = "shared::Color")]
+        struct ColorDef {
+            #[bitgen(bits = 5)]
+            r: u8,
+            #[bitgen(bits = 6)]
+            g: u8,
+            #[bitgen(bits = 5)]
+            b: u8,
+        }
+
+        #[derive(BitType)]
+        #[bitgen(remote = "shared::Brush")]
+        enum BrushDef {
+            Eraser,
+            Pen(u8),
+            Spray { radius: u8, density: u8 },
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Stroke {
+            #[bitgen(with = "ColorDef")]
+            color: shared::Color,
+            #[bitgen(bits = 4)]
+            width: u8,
+        }
+
+        assert_eq!(Stroke::BITS, 20);
+        let stroke = Stroke {
+            color: shared::Color { r: 31, g: 40, b: 3 },
+            width: 9,
+        };
+        let bit_stroke = Bit::from(stroke);
+        assert_eq!(bit_stroke.access().extract(), stroke);
+        let color = bit!(bit_stroke.color);
+        assert_eq!(color.extract().into_remote(), stroke.color);
+
+        let brushes = [
+            shared::Brush::Eraser,
+            shared::Brush::Pen(3),
+            shared::Brush::Spray {
+                radius: 5,
+                density: 1,
+            },
+        ];
+        for brush in brushes {
+            let bit_brush = Bit::from(BrushDef::from_remote(&brush));
+            assert_eq!(bit_brush.access().extract().into_remote(), brush);
+        }
+    }
+
+    #[test]
+    fn test_set_variant() {
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        enum Command {
+            Idle,
+            Move(U<4>),
+            Jump { to: U<3>, far: bool },
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        #[bitgen(tag_at = "end")]
+        enum Trailer {
+            A(U<5>),
+            B(bool),
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Program {
+            id: U<2>,
+            commands: [Command; 3],
+            next: Option<Command>,
+            trailer: Trailer,
+        }
+
+        let mut bit_program = Bit::from(Program {
+            id: ubits(1),
+            commands: [Command::Idle; 3],
+            next: None,
+            trailer: Trailer::A(ubits(17)),
+        });
+
+        bit!(mut bit_program.commands[1] = ?Move(Command_Move(ubits(9))));
+        bit!(mut bit_program.commands[2] = ?Jump(Command_Jump { to: ubits(5), far: true }));
+        bit!(mut bit_program.next = ?Some(Command::Move(ubits(3))));
+        bit!(mut bit_program.trailer = ?B(Trailer_B(true)));
+        assert_eq!(
+            bit!(bit_program).extract(),
+            Program {
+                id: ubits(1),
+                commands: [
+                    Command::Idle,
+                    Command::Move(ubits(9)),
+                    Command::Jump {
+                        to: ubits(5),
+                        far: true
+                    }
+                ],
+                next: Some(Command::Move(ubits(3))),
+                trailer: Trailer::B(true),
+            }
+        );
+
+        // Through a maybe accessor the variant only changes while the path exists.
+        assert_eq!(bit!(mut bit_program.next?Some = ?Idle), Ok(()));
+        assert_eq!(bit!(bit_program.next).extract(), Some(Command::Idle));
+        bit!(mut bit_program.next = ?None);
+        assert_eq!(
+            bit!(mut bit_program.next?Some = ?Idle),
+            Err(VariantMismatch {
+                expected: 1,
+                found: 0
+            })
+        );
+        assert_eq!(bit!(bit_program.next).extract(), None);
+
+        bit!(mut bit_program.commands[1] = ?Idle);
+        assert_eq!(bit!(bit_program.commands[1]).extract(), Command::Idle);
+        assert_eq!(bit!(bit_program.id).extract(), ubits(1));
+    }
+
+    #[test]
+    fn test_variant_mismatch() {
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        enum Shape {
+            Dot,
+            Line(U<4>),
+            Rect { w: U<3>, h: U<3> },
+        }
+
+        let mut bit_shapes = Bit::from([Some(Shape::Dot), None]);
+        let line = bit!(mut bit_shapes[0]?Some?Line.0);
+        let mismatch = VariantMismatch {
+            expected: 1,
+            found: 0,
+        };
+        assert_eq!(line.insert(ubits(5)), Err(mismatch));
+        assert_eq!(line.map(|length| length), Err(mismatch));
+        assert_eq!(
+            mismatch.to_string(),
+            "expected the variant with tag 1, found tag 0"
+        );
+        assert_eq!(bit!(bit_shapes).extract(), [Some(Shape::Dot), None]);
+
+        let line = bit!(mut bit_shapes[0]?Some?Line);
+        assert_eq!(line.insert_or_set_variant(Shape_Line(ubits(5))), Ok(()));
+        assert_eq!(bit!(mut bit_shapes[0]?Some?Line.0).insert(ubits(6)), Ok(()));
+        assert_eq!(
+            bit!(bit_shapes).extract(),
+            [Some(Shape::Line(ubits(6))), None]
+        );
+
+        // The outer `None` isn't switched, only the last variant in the path is.
+        let rect = bit!(mut bit_shapes[1]?Some?Rect);
+        assert_eq!(
+            rect.insert_or_set_variant(Shape_Rect {
+                w: ubits(1),
+                h: ubits(2)
+            }),
+            Err(VariantMismatch {
+                expected: 1,
+                found: 0
+            })
+        );
+        assert_eq!(bit!(bit_shapes[1]).extract(), None);
+
+        let mut bit_rect = Bit::from(Shape::Rect {
+            w: ubits(1),
+            h: ubits(2),
+        });
+        let line = bit!(mut bit_rect?Line);
+        assert_eq!(line.insert_or_set_variant(Shape_Line(ubits(3))), Ok(()));
+        assert_eq!(bit!(bit_rect).extract(), Shape::Line(ubits(3)));
+    }
+
+    #[test]
+    fn test_accessor_operations() {
+        let mut bit_counters = Bit::from((ubits::<5>(3), [U::<5>::new(7); 4], Some(ubits::<5>(1))));
+        let first = bit!(mut bit_counters.0);
+        assert_eq!(first.replace(ubits(9)), ubits(3));
+        assert_eq!(first.take(), ubits(9));
+        assert_eq!(first.compare_and_set(ubits(1), ubits(2)), Err(ubits(0)));
+        assert_eq!(first.compare_and_set(ubits(0), ubits(2)), Ok(ubits(0)));
+        first.update(|value| *value += ubits(20));
+        assert_eq!(bit!(bit_counters.0).extract(), ubits(22));
+
+        let index = 2;
+        let counter = bit!(mut bit_counters.1[index]);
+        assert_eq!(counter.replace(ubits(8)), ubits(7));
+        counter.update(|value| *value *= ubits(2));
+        assert_eq!(bit!(bit_counters.1[2]).extract(), ubits(16));
+
+        let maybe = bit!(mut bit_counters.2?Some);
+        assert_eq!(maybe.replace(ubits(4)), Ok(ubits(1)));
+        assert_eq!(maybe.compare_and_set(ubits(4), ubits(5)), Ok(Ok(ubits(4))));
+        assert_eq!(maybe.update(|value| *value += ubits(1)), Ok(()));
+        assert_eq!(bit!(bit_counters.2).extract(), Some(ubits(6)));
+        bit!(mut bit_counters.2).insert(None);
+        let mismatch = VariantMismatch {
+            expected: 1,
+            found: 0,
+        };
+        let maybe = bit!(mut bit_counters.2?Some);
+        assert_eq!(maybe.take(), Err(mismatch));
+        assert_eq!(maybe.update(|value| *value = ubits(1)), Err(mismatch));
+        assert_eq!(bit!(bit_counters.2).extract(), None);
+
+        // Swapping inside one container and across two.
+        let root = bit_counters.access_mut();
+        let first = Accessor::get::<0>(root.clone());
+        let last = Accessor::get_dyn(Accessor::get::<1>(root), 3);
+        first.swap(&last);
+        assert_eq!(bit!(bit_counters.0).extract(), ubits(7));
+        assert_eq!(bit!(bit_counters.1[3]).extract(), ubits(22));
+
+        let mut bit_other = Bit::from(Some(ubits::<5>(30)));
+        let first = bit!(mut bit_counters.0);
+        let other = bit!(mut bit_other?Some);
+        assert_eq!(first.swap(&other), Ok(()));
+        assert_eq!(bit!(bit_counters.0).extract(), ubits(30));
+        assert_eq!(bit!(bit_other).extract(), Some(ubits(7)));
+
+        bit!(mut bit_other).insert(None);
+        let first = bit!(mut bit_counters.0);
+        let other = bit!(mut bit_other?Some);
+        assert_eq!(first.swap(&other), Err(mismatch));
+        assert_eq!(bit!(bit_counters.0).extract(), ubits(30));
+        assert_eq!(bit!(bit_other).extract(), None);
+    }
+
+    #[test]
+    fn test_try_get_dyn() {
+        let mut bit_grid = Bit::from([[ubits::<3>(1); 4], [ubits(2); 4]]);
+        for i in 0..3 {
+            let row = bit!(bit_grid[?i]);
+            assert_eq!(
+                row.map(|row| row.extract()),
+                [[ubits(1); 4], [ubits(2); 4]].get(i).copied()
+            );
+        }
+        assert_eq!(
+            bit!(bit_grid[?1][?3]).map(|cell| cell.extract()),
+            Some(ubits(2))
+        );
+        assert!(bit!(bit_grid[?1][?4]).is_none());
+        assert!(bit!(bit_grid[?2][?0]).is_none());
+
+        bit!(mut bit_grid[?0][3]).unwrap().insert(ubits(7));
+        assert_eq!(bit!(bit_grid[0][3]).extract(), ubits(7));
+
+        let mut sum = 0;
+        let row = bit!(bit_grid[1]);
+        for i in 0..row.len() {
+            sum += unsafe { Accessor::get_dyn_unchecked(row.clone(), i) }
+                .extract()
+                .extract_underlying();
+        }
+        assert_eq!(sum, 8);
+
+        let bit_option = Bit::from(Some([true, false]));
+        assert_eq!(
+            bit!(bit_option?Some[?1]).map(|b| b.extract()),
+            Some(Some(false))
+        );
+        assert!(bit!(bit_option?Some[?2]).is_none());
+    }
+
+    #[test]
+    fn test_range_access() {
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Samples {
+            id: U<3>,
+            values: [U<5>; 8],
+        }
+
+        let mut bit_samples = Bit::from(Samples {
+            id: ubits(2),
+            values: [
+                ubits(0),
+                ubits(1),
+                ubits(2),
+                ubits(3),
+                ubits(4),
+                ubits(5),
+                ubits(6),
+                ubits(7),
+            ],
+        });
+        let middle = bit!(bit_samples.values[2..5]);
+        assert_eq!(middle.len(), 3);
+        let mut out = [U::new(0); 3];
+        middle.extract_into(&mut out);
+        assert_eq!(out, [ubits(2), ubits(3), ubits(4)]);
+        let reversed: Vec<_> = middle.iter().rev().map(|value| value.extract()).collect();
+        assert_eq!(reversed, [ubits(4), ubits(3), ubits(2)]);
+        let index = 1;
+        assert_eq!(bit!(bit_samples.values[2..5][index]).extract(), ubits(3));
+        assert_eq!(bit!(bit_samples.values[5..][index]).extract(), ubits(6));
+        assert_eq!(bit!(bit_samples.values[..]).len(), 8);
+        assert_eq!(bit!(bit_samples.values[1..=2]).len(), 2);
+
+        bit!(mut bit_samples.values[..3]).fill(ubits(31));
+        bit!(mut bit_samples.values[6..]).copy_from_slice(&[ubits(10), ubits(11)]);
+        assert_eq!(
+            bit!(bit_samples).extract(),
+            Samples {
+                id: ubits(2),
+                values: [
+                    ubits(31),
+                    ubits(31),
+                    ubits(31),
+                    ubits(3),
+                    ubits(4),
+                    ubits(5),
+                    ubits(10),
+                    ubits(11)
+                ],
+            }
+        );
+
+        let mut bit_vec: BitVec<U<3>> = (0..20).map(|i| U::new(i % 8)).collect();
+        let mut sums = Vec::new();
+        for start in (0..20).step_by(8) {
+            let chunk = bit_vec.access_range(start..(start + 8).min(20));
+            sums.push(
+                chunk
+                    .iter()
+                    .map(|value| value.extract().extract_underlying())
+                    .sum::<u8>(),
+            );
+        }
+        assert_eq!(sums, [28, 28, 6]);
+        bit_vec.access_range_mut(4..6).fill(ubits(0));
+        assert_eq!(bit_vec.get(3), Some(ubits(3)));
+        assert_eq!(bit_vec.get(4), Some(ubits(0)));
+        assert_eq!(bit_vec.get(5), Some(ubits(0)));
+        assert_eq!(bit_vec.get(6), Some(ubits(6)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_range_out_of_bounds() {
+        let bit_array = Bit::from([false; 4]);
+        bit!(bit_array[2..5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn test_range_inclusive_end_overflow() {
+        let bit_array = Bit::from([false; 4]);
+        bit!(bit_array[2..=usize::MAX]);
+    }
+
+    #[test]
+    fn test_for_each_mut() {
+        let mut bit_matrix = Bit::from([[ubits::<4>(1); 3]; 2]);
+        let mut next = 0;
+        bit!(mut bit_matrix).for_each_mut(|mut row| {
+            row.for_each_mut(|cell| {
+                cell.insert(U::new(next));
+                next += 1;
+            })
+        });
+        assert_eq!(
+            bit!(bit_matrix).extract(),
+            [
+                [ubits(0), ubits(1), ubits(2)],
+                [ubits(3), ubits(4), ubits(5)]
+            ]
+        );
+
+        let row = bit!(bit_matrix[1]);
+        let mut iter = row.iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next_back().map(|cell| cell.extract()), Some(ubits(5)));
+        assert_eq!(iter.next().map(|cell| cell.extract()), Some(ubits(3)));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next_back().map(|cell| cell.extract()), Some(ubits(4)));
+        assert!(iter.next().is_none());
+
+        let mut bit_vec: BitVec<U<4>> = (0..6).map(U::new).collect();
+        bit_vec
+            .access_range_mut(2..5)
+            .for_each_mut(|cell| cell.map(|value| value + ubits(8)));
+        let values: Vec<_> = (0..6).filter_map(|i| bit_vec.get(i)).collect();
+        assert_eq!(
+            values,
+            [
+                ubits(0),
+                ubits(1),
+                ubits(10),
+                ubits(11),
+                ubits(12),
+                ubits(5)
+            ]
+        );
+
+        let mut bit_flags = Bit::from([false; 10]);
+        let mut flags = bit!(mut bit_flags);
+        let mut iter = flags.iter_mut();
+        assert_eq!(iter.len(), 10);
+        if let Some(last) = iter.next_back() {
+            last.insert(true);
+        }
+        let mut set = 0;
+        while let Some(flag) = iter.next() {
+            if set == 3 {
+                break;
+            }
+            flag.insert(true);
+            set += 1;
+        }
+        assert_eq!(iter.len(), 5);
+        assert_eq!(
+            bit!(bit_flags).extract(),
+            [true, true, true, false, false, false, false, false, false, true]
+        );
+
+        let bit_left = Bit::from([true, false, true]);
+        let bit_right = Bit::from([ubits::<2>(1), ubits(2), ubits(3), ubits(0)]);
+        let pairs: Vec<_> = bit!(bit_left)
+            .zip(&bit!(bit_right))
+            .map(|(left, right)| (left.extract(), right.extract()))
+            .collect();
+        assert_eq!(
+            pairs,
+            [(true, ubits(1)), (false, ubits(2)), (true, ubits(3))]
+        );
+    }
+
+    #[test]
+    fn test_layout() {
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Header {
+            #[bitgen(bits = 4)]
+            version: u8,
+            #[bitgen(pad = 2)]
+            flags: [bool; 3],
+            #[bitgen(skip)]
+            _cached: u32,
+            kind: Kind,
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        #[bitgen(tag_bits = 2, tag_at = "end")]
+        enum Kind {
+            Empty,
+            Full,
+            Data {
+                len: U<3>,
+                last: bool,
+            },
+            #[bitgen(other)]
+            Unknown(U<2>),
+        }
+
+        let layout = Header::layout();
+        assert_eq!(layout.bits, 15);
+        assert!(layout.type_name.ends_with("Header"));
+        assert_eq!(
+            layout
+                .fields()
+                .iter()
+                .map(|field| (field.name, field.range()))
+                .collect::<Vec<_>>(),
+            [("version", 0..4), ("flags", 6..9), ("kind", 9..15)]
+        );
+
+        let version = layout.field("version").unwrap();
+        assert_eq!(version.type_name, "u8");
+        assert_eq!(version.layout.shape, Shape::Uint);
+        assert_eq!(version.layout, U::<4>::layout());
+        assert_eq!(
+            layout.field("flags").unwrap().layout.shape,
+            Shape::Array {
+                len: 3,
+                element: Box::new(bool::layout())
+            }
+        );
+        assert!(layout.field("_cached").is_none());
+
+        let kind = &layout.field("kind").unwrap().layout;
+        assert!(matches!(
+            kind.shape,
+            Shape::Enum {
+                tag_offset: 4,
+                tag_bits: 2,
+                ..
+            }
+        ));
+        assert_eq!(
+            kind.variants()
+                .iter()
+                .map(|variant| (variant.name, variant.tag))
+                .collect::<Vec<_>>(),
+            [
+                ("Empty", Some(0)),
+                ("Full", Some(1)),
+                ("Data", Some(2)),
+                ("Unknown", None)
+            ]
+        );
+        let data = kind.variant("Data").unwrap();
+        assert_eq!(
+            data.fields
+                .iter()
+                .map(|field| (field.name, field.range()))
+                .collect::<Vec<_>>(),
+            [("len", 0..3), ("last", 3..4)]
+        );
+        assert_eq!(kind.variant_of_tag(2).unwrap().name, "Data");
+        assert_eq!(kind.variant_of_tag(3).unwrap().name, "Unknown");
+        assert_eq!(kind.variant("Unknown").unwrap().fields[0].range(), 4..6);
+
+        let option = <(bool, Option<I<5>>)>::layout();
+        assert_eq!(option.field("1").unwrap().offset, 1);
+        let some = option.field("1").unwrap().layout.variant("Some").unwrap();
+        assert_eq!(some.fields[0].range(), 1..6);
+        assert_eq!(some.fields[0].layout.shape, Shape::Int);
+        assert_eq!(Gray::<U<3>>::layout().shape, Shape::Encoded(Encoding::Gray));
+    }
+
+    #[test]
+    fn test_opaque_layout() {
+        /// A 12 bit color with a hand-written `BitType`, which opts in to an opaque layout.
+        #[derive(PartialEq, Debug, Clone, Copy)]
+        struct Color(u16);
+
+        impl BitLayout for Color {}
+
+        impl BitType for Color {
+            const BITS: usize = 12;
+
+            fn from_aligned(aligned: &Self, slice: &mut [u8], offset: usize) {
+                U::<12>::from_aligned(&ubits(aligned.0), slice, offset)
+            }
+
+            fn to_aligned(slice: &[u8], offset: usize) -> Self {
+                Color(U::<12>::to_aligned(slice, offset).extract_underlying())
+            }
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Pixel {
+            lit: bool,
+            color: Color,
+        }
+
+        #[derive(Default)]
+        struct Opaques(Vec<(&'static str, usize, Vec<u8>)>);
+
+        impl BitVisitor for Opaques {
+            fn visit_bool(&mut self, _: bool) {}
+
+            fn visit_uint(&mut self, _: usize, _: u128) {}
+
+            fn visit_int(&mut self, _: usize, _: i128) {}
+
+            fn visit_opaque(&mut self, name: &'static str, bits: usize, bytes: &[u8]) {
+                self.0.push((name, bits, bytes.to_vec()));
+            }
+        }
+
+        let color = Pixel::layout().field("color").unwrap().clone();
+        assert_eq!(color.range(), 1..13);
+        assert_eq!(color.layout.shape, Shape::Opaque);
+
+        let mut bit_pixel = Bit::from(Pixel {
+            lit: true,
+            color: Color(0xa5c),
+        });
+        assert_eq!(
+            bit_pixel.get_path("color"),
+            Ok(DynValue::Opaque {
+                bits: 12,
+                bytes: vec![0x5c, 0x0a]
+            })
+        );
+        bit_pixel
+            .set_path(
+                "color",
+                DynValue::Opaque {
+                    bits: 12,
+                    bytes: vec![0x21, 0x03],
+                },
+            )
+            .unwrap();
+        assert_eq!(bit!(bit_pixel.color).extract(), Color(0x321));
+        assert_eq!(
+            bit_pixel.set_path(
+                "color",
+                DynValue::Opaque {
+                    bits: 12,
+                    bytes: vec![0x00, 0x10],
+                },
+            ),
+            Err(PathError::Overflow { bits: 12 })
+        );
+
+        let mut opaques = Opaques::default();
+        bit_pixel.visit(&mut opaques);
+        assert_eq!(opaques.0.len(), 1);
+        assert!(opaques.0[0].0.ends_with("Color"));
+        assert_eq!(opaques.0[0].1, 12);
+        assert_eq!(opaques.0[0].2, [0x21, 0x03]);
+    }
+
+    #[test]
+    fn test_path_access() {
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Packet {
+            header: Header,
+            #[bitgen(bits = 5)]
+            offset: i8,
+            checksum: Option<Gray<U<4>>>,
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Header {
+            flags: [bool; 4],
+            kind: Kind,
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        enum Kind {
+            Ping,
+            Data { len: U<3>, last: bool },
+        }
+
+        let packet = Packet {
+            header: Header {
+                flags: [false, true, false, true],
+                kind: Kind::Data {
+                    len: ubits(5),
+                    last: true,
+                },
+            },
+            offset: -3,
+            checksum: Some(Gray(ubits(9))),
+        };
+        let mut bit_packet = Bit::from(packet);
+
+        assert_eq!(
+            bit_packet.get_path("header.flags[3]"),
+            Ok(DynValue::Bool(true))
+        );
+        assert_eq!(
+            bit_packet.get_path("header.kind?Data.len"),
+            Ok(DynValue::Uint { bits: 3, value: 5 })
+        );
+        assert_eq!(
+            bit_packet.get_path("offset"),
+            Ok(DynValue::Int { bits: 5, value: -3 })
+        );
+        assert_eq!(
+            bit_packet.get_path("checksum?Some.0").unwrap().as_uint(),
+            Some(9)
+        );
+        let kind = bit_packet.get_path("header.kind").unwrap();
+        assert_eq!(kind.to_string(), "Data { len: 5, last: true }");
+        assert_eq!(
+            bit_packet.get_path("header").unwrap().field("flags"),
+            Some(&DynValue::Array(
+                [false, true, false, true]
+                    .into_iter()
+                    .map(DynValue::Bool)
+                    .collect()
+            ))
+        );
+
+        bit_packet
+            .set_path("header.flags[0]", DynValue::Bool(true))
+            .unwrap();
+        bit_packet
+            .set_path("offset", DynValue::Int { bits: 5, value: 15 })
+            .unwrap();
+        bit_packet
+            .set_path("checksum?Some.0", DynValue::Uint { bits: 4, value: 14 })
+            .unwrap();
+        bit_packet
+            .set_path(
+                "header.kind",
+                DynValue::Variant {
+                    name: "Ping".into(),
+                    fields: Vec::new(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            bit!(bit_packet).extract(),
+            Packet {
+                header: Header {
+                    flags: [true, true, false, true],
+                    kind: Kind::Ping,
+                },
+                offset: 15,
+                checksum: Some(Gray(ubits(14))),
+            }
+        );
+
+        assert_eq!(
+            bit_packet.get_path("header.kind?Data.len"),
+            Err(PathError::VariantMismatch {
+                expected: "Data",
+                found: "Ping"
+            })
+        );
+        assert_eq!(
+            bit_packet.get_path("header.flags[4]"),
+            Err(PathError::OutOfBounds { index: 4, len: 4 })
+        );
+        assert!(matches!(
+            bit_packet.get_path("header.size"),
+            Err(PathError::NoField { .. })
+        ));
+        assert_eq!(
+            bit_packet.get_path("header..flags"),
+            Err(PathError::Syntax { position: 7 })
+        );
+        assert_eq!(
+            bit_packet.set_path("offset", DynValue::Int { bits: 8, value: 16 }),
+            Err(PathError::Overflow { bits: 5 })
+        );
+        assert!(matches!(
+            bit_packet.set_path("header.flags", DynValue::Bool(false)),
+            Err(PathError::TypeMismatch { .. })
+        ));
+
+        // A value that fails halfway leaves the bits as they were.
+        let before = bit!(bit_packet).extract();
+        assert_eq!(
+            bit_packet.set_path(
+                "header.kind",
+                DynValue::Variant {
+                    name: "Data".into(),
+                    fields: vec![("len".into(), DynValue::Uint { bits: 4, value: 9 })],
+                },
+            ),
+            Err(PathError::Overflow { bits: 3 })
+        );
+        assert_eq!(
+            bit_packet.set_path(
+                "header",
+                DynValue::Struct(vec![
+                    (
+                        "flags".into(),
+                        DynValue::Array(vec![DynValue::Bool(false); 4])
+                    ),
+                    ("kind".into(), DynValue::Bool(true)),
+                ]),
+            ),
+            Err(PathError::TypeMismatch {
+                type_name: std::any::type_name::<Kind>()
+            })
+        );
+        assert_eq!(bit!(bit_packet).extract(), before);
+
+        let mut bit_vec: BitVec<Header> = (0..3)
+            .map(|i| Header {
+                flags: [i == 1; 4],
+                kind: Kind::Ping,
+            })
+            .collect();
+        assert_eq!(
+            bit_vec.access(1).get_path("flags[2]"),
+            Ok(DynValue::Bool(true))
+        );
+        bit_vec
+            .access_mut(2)
+            .set_path(
+                "kind",
+                DynValue::Variant {
+                    name: "Data".into(),
+                    fields: vec![("len".into(), DynValue::Uint { bits: 3, value: 7 })],
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            bit_vec.get(2).map(|header| header.kind),
+            Some(Kind::Data {
+                len: ubits(7),
+                last: false
+            })
+        );
+    }
+
+    #[test]
+    fn test_visitor() {
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Sample {
+            id: U<5>,
+            #[bitgen(bits = 4)]
+            delta: i16,
+            pair: (bool, ZigZag<I<4>>),
+            readings: [Option<U<3>>; 2],
+            status: Status,
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        #[bitgen(tag_bits = 2)]
+        enum Status {
+            Idle,
+            Busy(U<4>),
+            #[bitgen(other)]
+            Unknown(U<2>),
+        }
+
+        /// Writes what it visits as tokens.
+        #[derive(Default)]
+        struct Trace(Vec<String>);
+
+        impl BitVisitor for Trace {
+            fn visit_bool(&mut self, value: bool) {
+                self.0.push(value.to_string());
+            }
+
+            fn visit_uint(&mut self, width: usize, value: u128) {
+                self.0.push(format!("u{}:{}", width, value));
+            }
+
+            fn visit_int(&mut self, width: usize, value: i128) {
+                self.0.push(format!("i{}:{}", width, value));
+            }
+
+            fn visit_struct_begin(&mut self, _: &'static str, fields: usize) {
+                self.0.push(format!("{{{}", fields));
+            }
+
+            fn visit_field(&mut self, name: &'static str) {
+                self.0.push(format!("{}=", name));
+            }
+
+            fn visit_struct_end(&mut self) {
+                self.0.push("}".into());
+            }
+
+            fn visit_enum_variant(&mut self, _: &'static str, variant: &'static str, tag: u32) {
+                self.0.push(format!("{}#{}", variant, tag));
+            }
+
+            fn visit_enum_end(&mut self) {
+                self.0.push(";".into());
+            }
+
+            fn visit_unknown_tag(&mut self, _: &'static str, tag: u32) {
+                self.0.push(format!("?#{}", tag));
+            }
+
+            fn visit_array(&mut self, len: usize) {
+                self.0.push(format!("[{}", len));
+            }
+
+            fn visit_array_end(&mut self) {
+                self.0.push("]".into());
+            }
+
+            fn visit_option(&mut self, is_some: bool) {
+                self.0.push(if is_some { "Some(" } else { "None(" }.into());
+            }
+
+            fn visit_option_end(&mut self) {
+                self.0.push(")".into());
+            }
+        }
+
+        let sample = Sample {
+            id: ubits(19),
+            delta: -5,
+            pair: (true, ZigZag(ibits(-2))),
+            readings: [None, Some(ubits(6))],
+            status: Status::Busy(ubits(9)),
+        };
+        let bit_sample = Bit::from(sample);
+        let mut trace = Trace::default();
+        bit_sample.visit(&mut trace);
+        assert_eq!(
+            trace.0.join(" "),
+            "{5 id= u5:19 delta= i4:-5 pair= {2 0= true 1= i4:-2 } readings= [2 None( ) Some( u3:6 ) ] \
+             status= Busy#1 0= u4:9 ; }"
+        );
+
+        let mut trace = Trace::default();
+        bit!(bit_sample.readings[1]).visit(&mut trace);
+        assert_eq!(trace.0.join(" "), "Some( u3:6 )");
+
+        let statuses: BitVec<Status> = [Status::Idle, Status::Unknown(ubits(3))]
+            .into_iter()
+            .collect();
+        let mut trace = Trace::default();
+        for i in 0..statuses.len() {
+            statuses.access(i).visit(&mut trace);
+        }
+        assert_eq!(trace.0.join(" "), "Idle#0 ; Unknown#3 0= u2:3 ;");
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        #[bitgen(tag_bits = 2)]
+        enum Mode {
+            Off,
+            On(U<2>),
+        }
+
+        // The enum view of a union can hold a tag without a variant.
+        #[allow(dead_code)]
+        #[derive(BitType, Clone, Copy)]
+        union Word {
+            raw: U<4>,
+            mode: Mode,
+        }
+
+        // Every `Word` below is built from `raw`.
+        unsafe impl BitUnion for Word {}
+
+        let bit_word = Bit::from(Word { raw: ubits(0b1011) });
+        let mut trace = Trace::default();
+        bit_word.visit(&mut trace);
+        assert_eq!(trace.0.join(" "), "{2 raw= u4:11 mode= ?#3 }");
+    }
+
+    #[test]
+    fn test_bit_debug() {
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        struct Frame {
+            flags: [bool; 2],
+            #[bitgen(bits = 4)]
+            len: u8,
+            kind: Option<Kind>,
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        enum Kind {
+            Ack,
+            Data(I<3>),
+        }
+
+        let bit_frame = Bit::from(Frame {
+            flags: [true, false],
+            len: 9,
+            kind: Some(Kind::Data(ibits(-2))),
+        });
+        assert_eq!(
+            format!("{:?}", bit_frame),
+            "Bit { flags: [true, false], len: 9, kind: Some(Data(-2)) }"
+        );
+        assert_eq!(
+            format!("{:#?}", bit_frame),
+            "Bit<bitgen_test::tests::test_bit_debug::Frame> (11 bits) {
+     0..1  1     flags[0]            true
+     1..2  0     flags[1]            false
+     2..6  1001  len                 9
+     6..7  1     kind                Some (tag 1)
+     7..8  1     kind?Some.0         Data (tag 1)
+    8..11  110   kind?Some.0?Data.0  -2
+}"
+        );
+        assert_eq!(
+            Bit::from((ubits::<8>(0x2c), ubits::<8>(1)))
+                .raw()
+                .to_string(),
+            "Bit[00101100_00000001]"
+        );
+        assert_eq!(
+            format!("{:?}", Bit::from((true, ubits::<3>(5)))),
+            "Bit(true, 5)"
+        );
+        assert_eq!(format!("{:?}", Bit::from(ubits::<7>(100))), "Bit(100)");
+
+        /// A hand-written `BitType` with an opaque layout, shown as its bytes.
+        #[derive(PartialEq, Debug, Clone, Copy)]
+        struct Level(u8);
+
+        impl BitLayout for Level {}
+
+        impl BitType for Level {
+            const BITS: usize = 3;
+
+            fn from_aligned(aligned: &Self, slice: &mut [u8], offset: usize) {
+                U::<3>::from_aligned(&ubits(aligned.0), slice, offset)
+            }
+
+            fn to_aligned(slice: &[u8], offset: usize) -> Self {
+                Level(U::<3>::to_aligned(slice, offset).extract_underlying())
+            }
+        }
+
+        let bit_level = Bit::from(Level(5));
+        assert_eq!(format!("{:?}", bit_level), "Bit[00000101]");
+        assert_eq!(format!("{:#?}", bit_level), "Bit[00000101]");
+        assert_eq!(
+            format!("{:?}", Bit::from((Level(6), true))),
+            "Bit(0x06, true)"
+        );
+    }
+    #[test]
+    fn test_random() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut seen = [false; 8];
+        for _ in 0..256 {
+            let u: U<3> = rng.gen();
+            seen[u.extract_underlying() as usize] = true;
+
+            let i: I<5> = rng.gen();
+            assert!(i >= I::<5>::MIN && i <= I::<5>::MAX);
+        }
+        assert_eq!(seen, [true; 8]);
+    }
+
+    #[test]
+    fn test_arbitrary() {
+        use proptest::{arbitrary::any, test_runner::TestRunner};
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        #[bitgen(arbitrary)]
+        struct Packet {
+            a: bool,
+            b: U<5>,
+            c: I<3>,
+        }
+
+        #[derive(BitType, PartialEq, Debug, Clone, Copy)]
+        #[bitgen(arbitrary)]
+        enum Kind {
+            A,
+            B(U<3>, bool),
+            C { packet: Packet },
+        }
+
+        TestRunner::default()
+            .run(&any::<Bit<(Packet, Kind)>>(), |bit| {
+                let value = bit!(bit).extract();
+                assert_eq!(Bit::from(value).access().extract(), value);
+                Ok(())
+            })
+            .unwrap();
+
+        TestRunner::default()
+            .run(&any::<BitVec<I<7>>>(), |vec| {
+                for i in 0..vec.len() {
+                    let value = vec.get(i).unwrap();
+                    assert!(value >= I::<7>::MIN && value <= I::<7>::MAX);
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        let seen = std::cell::RefCell::new([false; 3]);
+        TestRunner::default()
+            .run(&any::<Kind>(), |kind| {
+                let bit_kind = Bit::from(kind);
+                assert_eq!(bit!(bit_kind).extract(), kind);
+                seen.borrow_mut()[match kind {
+                    Kind::A => 0,
+                    Kind::B(..) => 1,
+                    Kind::C { .. } => 2,
+                }] = true;
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(seen.into_inner(), [true; 3]);
+    }
+
     #[bench]
     fn bench_access(b: &mut Bencher) {
         let tuple = ([false; 22], [true; 22], [false; 22], [true; 22]);
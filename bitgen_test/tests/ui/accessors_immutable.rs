@@ -0,0 +1,20 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use bitgen::*;
+
+#[derive(BitType, Clone, Copy)]
+#[bitgen(accessors)]
+struct Header {
+    version: U<3>,
+    urgent: bool,
+}
+
+fn main() {
+    let bit_header = Bit::from(Header {
+        version: ubits(1),
+        urgent: false,
+    });
+    let mut header = bit_header.access();
+    header.set_urgent(true);
+}
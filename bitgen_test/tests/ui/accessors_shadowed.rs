@@ -0,0 +1,13 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use bitgen::*;
+
+#[derive(BitType, Clone, Copy)]
+#[bitgen(accessors)]
+struct Request {
+    id: U<4>,
+    path: U<12>,
+}
+
+fn main() {}
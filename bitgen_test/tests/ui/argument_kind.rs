@@ -0,0 +1,13 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use bitgen::*;
+
+#[derive(BitType)]
+#[bitgen(tag_bits = "4")]
+enum Kind {
+    A,
+    B,
+}
+
+fn main() {}
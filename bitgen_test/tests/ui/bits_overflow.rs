@@ -0,0 +1,12 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use bitgen::*;
+
+#[derive(BitType)]
+struct Small {
+    #[bitgen(bits = 9)]
+    value: u8,
+}
+
+fn main() {}
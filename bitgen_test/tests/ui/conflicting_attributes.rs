@@ -0,0 +1,13 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use bitgen::*;
+
+#[derive(BitType)]
+struct Cached {
+    id: U<4>,
+    #[bitgen(skip, bits = 4)]
+    cache: u32,
+}
+
+fn main() {}
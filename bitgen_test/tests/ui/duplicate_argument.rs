@@ -0,0 +1,12 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use bitgen::*;
+
+#[derive(BitType)]
+struct Flags {
+    #[bitgen(bits = 3, bits = 4)]
+    value: u8,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use bitgen::*;
+
+#[derive(BitType)]
+enum Empty {}
+
+fn main() {}
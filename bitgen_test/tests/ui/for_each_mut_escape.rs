@@ -0,0 +1,11 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use bitgen::*;
+
+fn main() {
+    let mut bit_array = Bit::from([false; 4]);
+    let mut children = Vec::new();
+    bit!(mut bit_array).for_each_mut(|bit| children.push(bit));
+    children[0].insert(true);
+}
@@ -0,0 +1,11 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use bitgen::*;
+
+fn main() {
+    let mut bit_array = Bit::from([false; 4]);
+    for bit in bit!(mut bit_array).iter() {
+        bit.insert(true);
+    }
+}
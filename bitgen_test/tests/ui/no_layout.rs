@@ -0,0 +1,24 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use bitgen::*;
+
+#[derive(Clone, Copy)]
+struct Level(u8);
+
+impl BitType for Level {
+    const BITS: usize = 3;
+
+    fn from_aligned(aligned: &Self, slice: &mut [u8], offset: usize) {
+        U::<3>::from_aligned(&ubits(aligned.0), slice, offset)
+    }
+
+    fn to_aligned(slice: &[u8], offset: usize) -> Self {
+        Level(U::<3>::to_aligned(slice, offset).extract_underlying())
+    }
+}
+
+fn main() {
+    let bit_level = Bit::from(Level(5));
+    let _ = bit_level.get_path("");
+}
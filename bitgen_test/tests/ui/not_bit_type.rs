@@ -0,0 +1,12 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use bitgen::*;
+
+#[derive(BitType)]
+struct Named {
+    id: U<4>,
+    name: String,
+}
+
+fn main() {}
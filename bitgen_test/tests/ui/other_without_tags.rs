@@ -0,0 +1,14 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use bitgen::*;
+
+#[derive(BitType)]
+enum Message {
+    Ping,
+    Pong,
+    #[bitgen(other)]
+    Unknown(U<1>),
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use bitgen::*;
+
+#[derive(BitType)]
+struct Register {
+    a: U<4>,
+    #[bitgen(at = 2)]
+    b: bool,
+}
+
+fn main() {}
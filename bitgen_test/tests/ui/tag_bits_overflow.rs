@@ -0,0 +1,14 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use bitgen::*;
+
+#[derive(BitType)]
+#[repr(u8)]
+#[bitgen(tag_bits = 2)]
+enum Opcode {
+    Nop,
+    Halt = 4,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use bitgen::*;
+
+#[derive(BitType)]
+#[bitgen(bits = 8)]
+struct Register {
+    a: U<4>,
+    b: bool,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use bitgen::*;
+
+#[derive(BitType, Clone, Copy)]
+union Register {
+    raw: U<8>,
+    low: U<4>,
+}
+
+fn main() {
+    let _ = Bit::from(Register { raw: ubits(3) });
+}
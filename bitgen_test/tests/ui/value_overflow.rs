@@ -0,0 +1,12 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use bitgen::*;
+
+#[derive(BitType)]
+struct Register {
+    #[bitgen(reserved = 2, value = 4)]
+    value: U<6>,
+}
+
+fn main() {}